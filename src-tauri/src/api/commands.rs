@@ -1,9 +1,9 @@
 use tauri::AppHandle;
 
 use crate::{
-    config::{self, Config},
-    models::audio_session::AudioSession,
-    services::volume_service,
+    config::{self, Config, ConfigError, Diagnostic, ProfileConfig},
+    models::{audio_device::AudioDevice, audio_session::AudioSession},
+    services::{device_service, profile_service, routing_service, volume_service},
     utils::logger,
 };
 
@@ -21,7 +21,7 @@ pub fn get_all_sessions() -> Vec<AudioSession> {
 
 #[tauri::command]
 pub fn get_session(session_name: &str) -> Option<AudioSession> {
-    return volume_service::get_sessions(session_name).into_iter().next();
+    return volume_service::get_session(session_name);
 }
 
 #[tauri::command]
@@ -30,18 +30,90 @@ pub fn get_session_volume(session_name: &str) -> i32 {
 }
 
 #[tauri::command]
-pub fn set_session_volume(session_name: &str, volume: i32) -> AudioSession {
-    return volume_service::set_session_volume(session_name, volume).expect("Failed to set volume.");
+pub fn set_session_volume(session_name: &str, volume: i32) -> Option<AudioSession> {
+    return volume_service::set_session_volume(session_name, volume);
 }
 
 
 #[tauri::command]
 pub fn toggle_session_mute(app_handle: AppHandle, session_name: &str) -> bool {
-    let session = volume_service::toggle_session_mute(session_name);
+    match volume_service::toggle_session_mute(session_name) {
+        Some(session) => {
+            events::emit_volume_change_event(&session, app_handle);
+            session.mute
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn set_session_device(session_id: &str, device_id: Option<String>) -> bool {
+    return routing_service::set_session_device(session_id, device_id.as_deref());
+}
+
+#[tauri::command]
+pub fn get_devices() -> Vec<AudioDevice> {
+    return device_service::get_devices();
+}
+
+#[tauri::command]
+pub fn get_device(device_id: &str) -> Option<AudioDevice> {
+    return device_service::get_device(device_id);
+}
+
+#[tauri::command]
+pub fn set_device_volume(app_handle: AppHandle, device_id: &str, volume: i32) -> Option<AudioDevice> {
+    let device = device_service::set_device_volume(device_id, volume);
+    if let Some(device) = &device {
+        events::emit_device_volume_change_event(device, app_handle);
+    }
+    return device;
+}
+
+#[tauri::command]
+pub fn set_device_mute(app_handle: AppHandle, device_id: &str, mute: bool) -> bool {
+    match device_service::set_device_mute(device_id, mute) {
+        Some(device) => {
+            events::emit_device_volume_change_event(&device, app_handle);
+            device.mute
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub fn toggle_device_mute(app_handle: AppHandle, device_id: &str) -> bool {
+    match device_service::toggle_device_mute(device_id) {
+        Some(device) => {
+            events::emit_device_volume_change_event(&device, app_handle);
+            device.mute
+        }
+        None => false,
+    }
+}
 
-    events::emit_volume_change_event(&session, app_handle);
+#[tauri::command]
+pub fn set_default_device(app_handle: AppHandle, device_id: &str) -> Option<AudioDevice> {
+    let device = device_service::set_default_device(device_id);
+    if let Some(device) = &device {
+        events::emit_device_change_event(device, app_handle);
+    }
+    return device;
+}
 
-    return session.mute;
+#[tauri::command]
+pub fn get_profiles() -> Vec<ProfileConfig> {
+    return profile_service::get_profiles();
+}
+
+#[tauri::command]
+pub fn save_profile(name: &str, app_handle: AppHandle) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    return profile_service::save_profile(name, &app_handle);
+}
+
+#[tauri::command]
+pub fn apply_profile(app_handle: AppHandle, name: &str) -> bool {
+    return profile_service::apply_profile(name, app_handle);
 }
 
 #[tauri::command]
@@ -50,8 +122,18 @@ pub fn get_config() -> Config {
 }
 
 #[tauri::command]
-pub fn set_config(config: Config, app_handle: AppHandle) {
-    config::set_config(config, &app_handle);
+pub fn set_config(config: Config, app_handle: AppHandle) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    return config::set_config(config, &app_handle);
+}
+
+#[tauri::command]
+pub fn validate_config(config: Config) -> Vec<Diagnostic> {
+    return config::validate_config(&config);
+}
+
+#[tauri::command]
+pub fn get_config_error() -> Option<ConfigError> {
+    return config::get_config_error();
 }
 
 #[tauri::command]
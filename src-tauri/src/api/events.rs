@@ -1,12 +1,23 @@
 use tauri::{AppHandle, Emitter, Manager};
 
-use crate::{config::Config, models::audio_session::AudioSession, services::window_service};
+use crate::{
+    config::{Config, ConfigError},
+    models::{audio_device::AudioDevice, audio_session::AudioSession, meter_update::MeterUpdate},
+    services::window_service,
+};
 
 #[derive(Clone, Copy)]
 pub enum AppEvent {
     VolumeChange,
+    SessionAdded,
+    SessionRemoved,
+    DeviceVolumeChange,
+    DeviceChange,
+    MicMuteChange,
+    MeterUpdate,
     MixerVisibilityChange,
     ConfigChange,
+    ConfigError,
     ThemeChange,
     WindowHidden,
 }
@@ -15,8 +26,15 @@ impl AppEvent {
     pub fn as_str(&self) -> &'static str {
         match self {
             AppEvent::VolumeChange => "volume-change-event",
+            AppEvent::SessionAdded => "session-added-event",
+            AppEvent::SessionRemoved => "session-removed-event",
+            AppEvent::DeviceVolumeChange => "device-volume-change-event",
+            AppEvent::DeviceChange => "device-change-event",
+            AppEvent::MicMuteChange => "mic-mute-change-event",
+            AppEvent::MeterUpdate => "meter-update-event",
             AppEvent::MixerVisibilityChange => "mixer-visibility-change-event",
             AppEvent::ConfigChange => "config-change-event",
+            AppEvent::ConfigError => "config-error-event",
             AppEvent::ThemeChange => "theme-change-event",
             AppEvent::WindowHidden => "window-hidden-event",
         }
@@ -35,6 +53,52 @@ pub fn emit_volume_change_event(audio_session: &AudioSession, app_handle: AppHan
     window_service::show_overlay(app_handle.clone());
 }
 
+// Changes made outside Maestro update the UI but don't pop up the overlay
+pub fn emit_external_volume_change_event(audio_session: &AudioSession, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::VolumeChange.as_str(), audio_session).unwrap();
+}
+
+pub fn emit_session_added_event(audio_session: &AudioSession, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::SessionAdded.as_str(), audio_session).unwrap();
+}
+
+pub fn emit_session_removed_event(audio_session: &AudioSession, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::SessionRemoved.as_str(), audio_session).unwrap();
+}
+
+pub fn emit_device_volume_change_event(audio_device: &AudioDevice, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::DeviceVolumeChange.as_str(), audio_device).unwrap();
+}
+
+// Carries the new default device
+pub fn emit_device_change_event(audio_device: &AudioDevice, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::DeviceChange.as_str(), audio_device).unwrap();
+
+    let mixer_visible = app_handle.get_webview_window("mixer").unwrap().is_visible().unwrap();
+    if mixer_visible {
+        return;
+    }
+
+    window_service::show_overlay(app_handle.clone());
+}
+
+// The mixer row updates through the device event, the overlay through the mic event
+pub fn emit_mic_mute_change_event(audio_device: &AudioDevice, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::DeviceVolumeChange.as_str(), audio_device).unwrap();
+    app_handle.emit(AppEvent::MicMuteChange.as_str(), audio_device).unwrap();
+
+    let mixer_visible = app_handle.get_webview_window("mixer").unwrap().is_visible().unwrap();
+    if mixer_visible {
+        return;
+    }
+
+    window_service::show_overlay(app_handle.clone());
+}
+
+pub fn emit_meter_update_event(meter_update: &MeterUpdate, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::MeterUpdate.as_str(), meter_update).unwrap();
+}
+
 pub fn emit_mixer_visibility_change_event(visible: bool, app_handle: AppHandle) {
     app_handle.emit(AppEvent::MixerVisibilityChange.as_str(), visible).unwrap();
 }
@@ -46,6 +110,10 @@ pub fn emit_config_change_event(config: &Config, app_handle: AppHandle) {
         .unwrap();
 }
 
+pub fn emit_config_error_event(error: &ConfigError, app_handle: AppHandle) {
+    app_handle.emit(AppEvent::ConfigError.as_str(), error).unwrap();
+}
+
 pub fn emit_window_hidden_event(app_handle: AppHandle) {
     app_handle.emit(AppEvent::WindowHidden.as_str(), ()).unwrap();
 }
@@ -1,26 +1,92 @@
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 use serde_json::to_string_pretty;
+use std::ffi::OsString;
 use std::fs;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use tauri::AppHandle;
-use yaml_rust::{Yaml, YamlEmitter, YamlLoader};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
 
-use crate::api::events::emit_config_change_event;
+use crate::api::events::{emit_config_change_event, emit_config_error_event};
 
+mod document;
+mod error;
+mod migrations;
+pub mod storage;
+mod validation;
+mod watcher;
 
+pub use error::{ConfigError, ConfigErrorKind};
+pub use migrations::CURRENT_VERSION;
+pub use validation::{validate_config, Diagnostic, Severity, MAX_METER_RATE};
+
+// Action is parsed as a models::keybind_action::KeybindAction, such as volume_up, set:<0-100> or profile:<name>
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KeybindConfig {
     pub key: String,
     pub action: String,
+    // Volume step for volume_up and volume_down, over the session's or group's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<i32>,
+}
+
+// How far volume_up and volume_down move a channel. Holding the key grows the step toward max, without one every
+// step is the same.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StepConfig {
+    #[serde(default = "default_step_size")]
+    pub size: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
+}
+
+fn default_step_size() -> i32 {
+    2
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveKind {
+    #[default]
+    Linear,
+    // Even steps in loudness rather than in signal level, like an audio taper pot
+    Logarithmic,
+    // Through `points`
+    Custom,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeDisplay {
+    #[default]
+    Percent,
+    Db,
+}
+
+// How app volume sliders map to the level Windows gets. Master and devices keep the taper Windows gives them.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct VolumeConfig {
+    #[serde(default)]
+    pub curve: CurveKind,
+    // [slider, volume] pairs, both 0-100 and rising, for the custom curve
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub points: Vec<[i32; 2]>,
+    #[serde(default)]
+    pub display: VolumeDisplay,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MixerConfig {
     pub enabled: bool,
     pub hotkey: Option<String>,
+    // Level meter updates per second while the mixer is open, 0 turns the meters off
+    #[serde(default = "default_meter_rate")]
+    pub meter_rate: u32,
+}
+
+fn default_meter_rate() -> u32 {
+    20
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,140 +96,628 @@ pub struct SystemConfig {
     pub theme: Option<String>,
 }
 
+// With no exe, title, pid or instance_id set, a session matches by name. Otherwise every one of those that is set
+// must match. Any alias matching the session name is also enough. See services::session_matcher.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SessionConfig {
     pub name: String,
     pub keybinds: Option<Vec<KeybindConfig>>,
+    // Other session names this entry covers, wildcards allowed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aliases: Option<Vec<String>>,
+    // Executable file name (discord.exe) or full path, wildcards allowed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    // Regex on the title of the app's window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_id: Option<String>,
+    // Playback device name (wildcards allowed) or endpoint id the app's audio is sent to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_device: Option<String>,
+    // Restore the last volume and mute when the app starts again, on unless set to false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remember: Option<bool>,
+    // Volume step for this entry's keybinds, over the global one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupMode {
+    // Scale members so the loudest lands on the new level, keeping the mix between them
+    #[default]
+    Relative,
+    // Set every member to the new level
+    Absolute,
+}
+
+// Several sessions that move together as one channel. Members are session entry names or session names.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupConfig {
+    pub name: String,
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub mode: GroupMode,
+    pub keybinds: Option<Vec<KeybindConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<i32>,
+}
+
+// One mixer channel in a profile: a session entry, group or session name
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProfileLevel {
+    pub name: String,
+    pub volume: i32,
+    pub mute: bool,
+}
+
+// Mixer levels applied together, from the tray, a profile:<name> keybind or the mixer
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileConfig {
+    pub name: String,
+    pub sessions: Vec<ProfileLevel>,
+    // Milliseconds to fade to the new levels over, applied at once when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fade: Option<u64>,
+}
+
+// Lowers the target channels while the trigger is playing, and puts them back once it has been quiet for `hold`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuckingConfig {
+    // Session entry, group or session name, such as discord
+    pub trigger: String,
+    pub targets: Vec<String>,
+    // Percent of their volume the targets lose
+    #[serde(default = "default_duck_amount")]
+    pub amount: i32,
+    // Milliseconds
+    #[serde(default = "default_duck_hold")]
+    pub hold: u64,
+    // Peak level from 0.0 to 1.0 the trigger has to reach to count as playing
+    #[serde(default = "default_duck_threshold")]
+    pub threshold: f32,
+}
+
+fn default_duck_amount() -> i32 {
+    50
+}
+
+fn default_duck_hold() -> u64 {
+    1000
+}
+
+fn default_duck_threshold() -> f32 {
+    0.05
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
+    #[serde(default = "migrations::current_version")]
+    pub version: u32,
     pub sessions: Vec<SessionConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GroupConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<ProfileConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ducking: Vec<DuckingConfig>,
+    #[serde(default)]
+    pub step: StepConfig,
+    #[serde(default)]
+    pub volume: VolumeConfig,
     pub mixer: MixerConfig,
     pub system: SystemConfig,
 }
 
+impl Default for MixerConfig {
+    fn default() -> Self {
+        MixerConfig {
+            enabled: true,
+            hotkey: Some("Ctrl + Shift + M".to_string()),
+            meter_rate: default_meter_rate(),
+        }
+    }
+}
+
+impl Default for StepConfig {
+    fn default() -> Self {
+        StepConfig {
+            size: default_step_size(),
+            max: None,
+        }
+    }
+}
+
+impl Default for SystemConfig {
+    fn default() -> Self {
+        SystemConfig {
+            autostart: true,
+            show_console: false,
+            theme: Some("dark".to_string()),
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn new(name: &str) -> Self {
+        SessionConfig {
+            name: name.to_string(),
+            keybinds: None,
+            aliases: None,
+            exe: None,
+            title: None,
+            pid: None,
+            instance_id: None,
+            output_device: None,
+            remember: None,
+            step: None,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CURRENT_VERSION,
+            sessions: vec![SessionConfig::new("master"), SessionConfig::new("other")],
+            groups: Vec::new(),
+            profiles: Vec::new(),
+            ducking: Vec::new(),
+            step: StepConfig::default(),
+            volume: VolumeConfig::default(),
+            mixer: MixerConfig::default(),
+            system: SystemConfig::default(),
+        }
+    }
+}
+
+const CONFIG_FILE_NAME: &str = "config.yaml";
+const STATE_FILE_NAME: &str = "state.json";
+const CONFIG_PATH_ARG: &str = "--config";
+const CONFIG_PATH_ENV: &str = "MAESTRO_CONFIG";
+const DEFAULT_CONFIG: &str = include_str!("../config.yaml");
+
+static CONFIG_PATH: OnceCell<PathBuf> = OnceCell::new();
 static CONFIG: Lazy<Arc<Mutex<Config>>> = Lazy::new(|| Arc::new(Mutex::new(load_config())));
+static CONFIG_ERROR: Lazy<Mutex<Option<ConfigError>>> = Lazy::new(|| Mutex::new(None));
+
+/// Resolve where config.yaml lives and make sure the file exists.
+/// Must run before the config is first read.
+pub fn init(app_handle: &AppHandle) {
+    let file_path = config_path_override()
+        .or_else(default_config_path)
+        .unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME));
+    log::info!("Using config file: {}", file_path.display());
 
-// Return all defined sessions except for 'other'
-pub fn get_defined_session_names() -> Vec<String> {
-    let config = get_config();
-    let session_names: Vec<String> = config
-        .sessions
-        .iter()
-        .filter(|session| session.name.to_lowercase() != "other")
-        .map(|session| session.name.clone())
-        .collect();
-    return session_names.iter().map(|name| name.to_lowercase()).collect();
+    let bundled_path = app_handle.path().resolve(CONFIG_FILE_NAME, BaseDirectory::Resource).ok();
+    let legacy_path = std::env::current_dir().ok().map(|dir| dir.join(CONFIG_FILE_NAME));
+    if let Err(e) = prepare_config_file(&file_path, bundled_path.as_deref(), legacy_path.as_deref()) {
+        log::error!("Failed to prepare config file {}: {}", file_path.display(), e);
+    }
+
+    if CONFIG_PATH.set(file_path).is_err() {
+        log::warn!("Config path already initialized");
+    }
+
+    Lazy::force(&CONFIG);
+    if let Some(error) = get_config_error() {
+        emit_config_error_event(&error, app_handle.clone());
+    }
 }
 
 pub fn get_config() -> Config {
     CONFIG.lock().unwrap().clone()
 }
 
-pub fn set_config(config: Config, app_handle: &AppHandle) {
+/// Problem found the last time config.yaml was loaded, if the app is running on a fallback config.
+pub fn get_config_error() -> Option<ConfigError> {
+    CONFIG_ERROR.lock().unwrap().clone()
+}
+
+/// Validate and save a config. Returns the remaining warnings on success, or every diagnostic
+/// if any of them is an error, in which case nothing is saved.
+pub fn set_config(config: Config, app_handle: &AppHandle) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
     let pretty_config = to_string_pretty(&config).unwrap();
     log::info!("Saving config:\n{}", pretty_config);
 
-    save_config(&config).unwrap();
+    let diagnostics = validate_config(&config);
+    if validation::has_errors(&diagnostics) {
+        log::warn!("Rejected config with {} diagnostics", diagnostics.len());
+        return Err(diagnostics);
+    }
+
+    if let Err(e) = save_config(&config) {
+        log::error!("Failed to save config: {}", e);
+        return Err(vec![Diagnostic {
+            path: String::new(),
+            severity: Severity::Error,
+            message: format!("Failed to save config: {}", e),
+        }]);
+    }
 
     let reloaded_config = load_config();
 
     let mut config_guard = CONFIG.lock().unwrap();
     *config_guard = reloaded_config;
+    drop(config_guard);
 
     emit_config_change_event(&config, app_handle.clone());
 
     log::info!("Config reload complete.");
+    Ok(diagnostics)
 }
 
+// Only the fields that changed are rewritten, so the user's comments and layout survive
 fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     let file_path = config_file_path()?;
-    let mut file_content = String::new();
-    if file_path.exists() {
-        fs::File::open(&file_path)?.read_to_string(&mut file_content)?;
+    let file_content = if file_path.exists() {
+        fs::read_to_string(&file_path)?
+    } else {
+        String::new()
+    };
+
+    let updated = serde_yaml::to_value(config)?;
+    let document = document::update_document(&file_content, &updated);
+    watcher::write_seen(&file_path, || storage::write_atomic(&file_path, &document, storage::BACKUP_COUNT))?;
+    Ok(())
+}
+
+// Never fails: a broken file is set aside and the app falls back to the newest backup that loads, or defaults
+fn load_config() -> Config {
+    let file_path = match config_file_path() {
+        Ok(file_path) => file_path,
+        Err(e) => {
+            log::error!("Failed to locate config: {}", e);
+            return Config::default();
+        }
+    };
+
+    match read_config(&file_path) {
+        Ok((config, file_version)) => {
+            accept_config(&file_path, &config, file_version);
+            config
+        }
+        Err(mut error) => {
+            log::error!("{}", error);
+            error.backup_path = keep_broken_config(&file_path).map(|path| path.display().to_string());
+            let config = recover_config(&file_path);
+            *CONFIG_ERROR.lock().unwrap() = Some(error);
+            config
+        }
     }
+}
 
-    let mut yaml_docs = YamlLoader::load_from_str(&file_content).unwrap_or_else(|_| vec![Yaml::Hash(yaml_rust::yaml::Hash::new())]);
-    let yaml_config = &mut yaml_docs[0];
+/// Watch config.yaml for edits made outside the app and apply them while running.
+pub fn watch(app_handle: &AppHandle) {
+    let file_path = match config_file_path() {
+        Ok(file_path) => file_path,
+        Err(e) => {
+            log::error!("Not watching config: {}", e);
+            return;
+        }
+    };
 
-    // Update the YAML with new values from the config
-    update_yaml_from_config(yaml_config, config);
+    let app_handle = app_handle.clone();
+    watcher::start(file_path, move || reload_config(&app_handle));
+}
 
-    let mut out_str = String::new();
+// Invalid edits are reported and rejected, leaving the running config untouched
+fn reload_config(app_handle: &AppHandle) {
+    let file_path = match config_file_path() {
+        Ok(file_path) => file_path,
+        Err(_) => return,
+    };
 
-    let mut emitter = YamlEmitter::new(&mut out_str);
-    emitter.dump(yaml_config)?;
+    let loaded = read_config(&file_path).and_then(|(config, file_version)| {
+        let diagnostics = validate_config(&config);
+        if validation::has_errors(&diagnostics) {
+            return Err(ConfigError::validation(&file_path, &diagnostics));
+        }
+        Ok((config, file_version))
+    });
 
-    fs::File::create(&file_path)?.write_all(out_str.as_bytes())?;
-    Ok(())
+    match loaded {
+        Ok((config, file_version)) => {
+            accept_config(&file_path, &config, file_version);
+            *CONFIG.lock().unwrap() = config.clone();
+            emit_config_change_event(&config, app_handle.clone());
+            log::info!("Config reloaded from disk.");
+        }
+        Err(error) => {
+            log::error!("Rejected config edit, keeping running config: {}", error);
+            *CONFIG_ERROR.lock().unwrap() = Some(error.clone());
+            emit_config_error_event(&error, app_handle.clone());
+        }
+    }
 }
 
-fn load_config() -> Config {
-    let file_path = config_file_path().unwrap();
-    let mut file_content = String::new();
-    fs::File::open(&file_path).unwrap().read_to_string(&mut file_content).unwrap();
+fn accept_config(file_path: &Path, config: &Config, file_version: u32) {
+    *CONFIG_ERROR.lock().unwrap() = None;
+    for diagnostic in validate_config(config) {
+        log::warn!("Config {}: {}", diagnostic.path, diagnostic.message);
+    }
 
-    let yaml_docs = YamlLoader::load_from_str(&file_content).unwrap();
-    let yaml_config = &yaml_docs[0];
+    if file_version >= CURRENT_VERSION {
+        return;
+    }
 
-    let config: Config = serde_yaml::from_value(yaml_to_value(yaml_config)).unwrap();
-    return config;
+    // Only saves rotate the backups otherwise. The file in its old schema has to be among them before it's rewritten.
+    match storage::rotate_backups(file_path, storage::BACKUP_COUNT) {
+        Ok(()) => upgrade_config_file(file_path, config, file_version),
+        Err(e) => log::error!("Not upgrading config without a backup of it, leaving it as is: {}", e),
+    }
 }
 
-fn config_file_path() -> Result<PathBuf, std::io::Error> {
-    Ok(std::env::current_dir()?.join("config.yaml"))
+// Returns the parsed config along with the schema version the file was written in
+fn read_config(file_path: &Path) -> Result<(Config, u32), ConfigError> {
+    let file_content = fs::read_to_string(file_path).map_err(|e| ConfigError::io(file_path, e))?;
+
+    let mut document: serde_yaml::Value = serde_yaml::from_str(&file_content).map_err(|e| ConfigError::parse(file_path, e))?;
+    let file_version = migrations::migrate(&mut document).map_err(|message| ConfigError::migration(file_path, message))?;
+
+    match serde_yaml::from_value(document) {
+        Ok(config) => Ok((config, file_version)),
+        Err(e) => {
+            // from_value has no source positions, so re-parse the original text to point at the offending line
+            if file_version == CURRENT_VERSION {
+                if let Err(located) = serde_yaml::from_str::<Config>(&file_content) {
+                    return Err(ConfigError::parse(file_path, located));
+                }
+            }
+            Err(ConfigError::parse(file_path, e))
+        }
+    }
+}
+
+// Rewrite the file in the current schema. The pre-migration file is already the newest backup.
+fn upgrade_config_file(file_path: &Path, config: &Config, file_version: u32) {
+    match save_config(config) {
+        Ok(()) => log::info!(
+            "Upgraded config from version {} to {}, previous file saved as {}",
+            file_version,
+            CURRENT_VERSION,
+            storage::backup_path(file_path, 1).display()
+        ),
+        Err(e) => log::error!("Failed to write upgraded config: {}", e),
+    }
 }
 
-fn yaml_to_value(yaml: &Yaml) -> serde_yaml::Value {
-    match yaml {
-        Yaml::Real(s) => serde_yaml::Value::Number(s.parse().unwrap()),
-        Yaml::Integer(i) => serde_yaml::Value::Number((*i).into()),
-        Yaml::String(s) => serde_yaml::Value::String(s.clone()),
-        Yaml::Boolean(b) => serde_yaml::Value::Bool(*b),
-        Yaml::Array(a) => serde_yaml::Value::Sequence(a.iter().map(yaml_to_value).collect()),
-        Yaml::Hash(h) => serde_yaml::Value::Mapping(h.iter().map(|(k, v)| (yaml_to_value(k), yaml_to_value(v))).collect()),
-        Yaml::Null => serde_yaml::Value::Null,
-        _ => unreachable!(),
+// Copied rather than moved, so the user can still fix config.yaml where it is
+fn keep_broken_config(file_path: &Path) -> Option<PathBuf> {
+    if !file_path.exists() {
+        return None;
+    }
+
+    let broken_path = storage::broken_path(file_path);
+    match fs::copy(file_path, &broken_path) {
+        Ok(_) => {
+            log::warn!("Saved invalid config as {}", broken_path.display());
+            Some(broken_path)
+        }
+        Err(e) => {
+            log::error!("Failed to save invalid config: {}", e);
+            None
+        }
     }
 }
 
-fn update_yaml_from_config(yaml: &mut Yaml, config: &Config) {
-    if let Yaml::Hash(ref mut hash) = *yaml {
-        update_yaml_field(hash, "sessions", &config.sessions);
-        update_yaml_field(hash, "mixer", &config.mixer);
-        update_yaml_field(hash, "system", &config.system);
+fn recover_config(file_path: &Path) -> Config {
+    for index in 1..=storage::BACKUP_COUNT {
+        let backup_path = storage::backup_path(file_path, index);
+        if let Ok((config, _)) = read_config(&backup_path) {
+            log::warn!("Using backup config {}", backup_path.display());
+            return config;
+        }
     }
+
+    log::warn!("No usable backup config, using defaults");
+    Config::default()
 }
 
-fn update_yaml_field<T: Serialize>(hash: &mut yaml_rust::yaml::Hash, key: &str, value: &T) {
-    let yaml_value = serde_yaml::to_value(value).unwrap();
-    let yaml_key = Yaml::String(key.to_string());
-    hash.insert(yaml_key, value_to_yaml(&yaml_value));
+/// Where runtime state such as remembered volumes is kept, next to config.yaml.
+pub fn state_file_path() -> Result<PathBuf, std::io::Error> {
+    Ok(config_file_path()?.with_file_name(STATE_FILE_NAME))
 }
 
-fn value_to_yaml(value: &serde_yaml::Value) -> Yaml {
-    match value {
-        serde_yaml::Value::Null => Yaml::Null,
-        serde_yaml::Value::Bool(b) => Yaml::Boolean(*b),
-        serde_yaml::Value::Number(n) => {
-            if let Some(i) = n.as_i64() {
-                Yaml::Integer(i)
-            } else if let Some(f) = n.as_f64() {
-                Yaml::Real(f.to_string())
-            } else {
-                Yaml::Null
-            }
+fn config_file_path() -> Result<PathBuf, std::io::Error> {
+    match CONFIG_PATH.get() {
+        Some(file_path) => Ok(file_path.clone()),
+        None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Config path not initialized")),
+    }
+}
+
+// Explicit path from `--config <path>`, `--config=<path>` or MAESTRO_CONFIG
+fn config_path_override() -> Option<PathBuf> {
+    path_override(std::env::args().skip(1), std::env::var_os(CONFIG_PATH_ENV))
+}
+
+// The flag wins over the environment variable
+fn path_override(mut args: impl Iterator<Item = String>, env_path: Option<OsString>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_PATH_ARG {
+            return args.next().map(PathBuf::from);
         }
-        serde_yaml::Value::String(s) => Yaml::String(s.clone()),
-        serde_yaml::Value::Sequence(seq) => Yaml::Array(seq.iter().map(value_to_yaml).collect()),
-        serde_yaml::Value::Mapping(m) => {
-            let mut hash = yaml_rust::yaml::Hash::new();
-            for (k, v) in m {
-                hash.insert(value_to_yaml(k), value_to_yaml(v));
-            }
-            Yaml::Hash(hash)
+        if let Some(value) = arg.strip_prefix(&format!("{}=", CONFIG_PATH_ARG)) {
+            return Some(PathBuf::from(value));
+        }
+    }
+
+    env_path.map(PathBuf::from)
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("maestro").join(CONFIG_FILE_NAME))
+}
+
+// First run: migrate a config left in the working directory by older versions,
+// otherwise seed from the bundled resource (or the copy compiled into the binary)
+fn prepare_config_file(file_path: &Path, bundled_path: Option<&Path>, legacy_path: Option<&Path>) -> Result<(), std::io::Error> {
+    if file_path.exists() {
+        return Ok(());
+    }
+
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if let Some(legacy_path) = legacy_path.filter(|path| path.exists() && Some(*path) != bundled_path) {
+        log::info!("Migrating config from {} to {}", legacy_path.display(), file_path.display());
+        fs::copy(legacy_path, file_path)?;
+        return Ok(());
+    }
+
+    match bundled_path.filter(|path| path.exists()) {
+        Some(bundled_path) => {
+            log::info!("Seeding config from {}", bundled_path.display());
+            fs::copy(bundled_path, file_path)?;
+        }
+        None => {
+            log::info!("Seeding config from built-in defaults");
+            fs::write(file_path, DEFAULT_CONFIG)?;
         }
-        serde_yaml::Value::Tagged(tagged) => value_to_yaml(&tagged.value),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("maestro-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn config_path_comes_from_the_flag_or_the_environment() {
+        let env_path = Some(OsString::from("env.yaml"));
+
+        assert_eq!(path_override(args(&["--config", "a.yaml"]), None), Some(PathBuf::from("a.yaml")));
+        assert_eq!(path_override(args(&["--debug", "--config=b.yaml"]), None), Some(PathBuf::from("b.yaml")));
+        assert_eq!(path_override(args(&[]), env_path.clone()), Some(PathBuf::from("env.yaml")));
+        assert_eq!(path_override(args(&["--config", "a.yaml"]), env_path), Some(PathBuf::from("a.yaml")));
+        assert_eq!(path_override(args(&["--debug"]), None), None);
+    }
+
+    #[test]
+    fn existing_config_is_left_alone() {
+        let dir = temp_dir("existing");
+        let file_path = dir.join(CONFIG_FILE_NAME);
+        let legacy_path = dir.join("legacy.yaml");
+        fs::write(&file_path, "mine\n").unwrap();
+        fs::write(&legacy_path, "legacy\n").unwrap();
+
+        prepare_config_file(&file_path, None, Some(&legacy_path)).unwrap();
+
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "mine\n");
+    }
+
+    #[test]
+    fn legacy_config_in_the_working_directory_is_migrated() {
+        let dir = temp_dir("legacy");
+        let file_path = dir.join("maestro").join(CONFIG_FILE_NAME);
+        let legacy_path = dir.join(CONFIG_FILE_NAME);
+        let bundled_path = dir.join("bundled.yaml");
+        fs::write(&legacy_path, "legacy\n").unwrap();
+        fs::write(&bundled_path, "bundled\n").unwrap();
+
+        prepare_config_file(&file_path, Some(&bundled_path), Some(&legacy_path)).unwrap();
+
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "legacy\n");
+        assert!(legacy_path.exists());
+    }
+
+    #[test]
+    fn first_run_is_seeded_from_the_bundled_config() {
+        let dir = temp_dir("bundled");
+        let file_path = dir.join("maestro").join(CONFIG_FILE_NAME);
+        let bundled_path = dir.join(CONFIG_FILE_NAME);
+        fs::write(&bundled_path, "bundled\n").unwrap();
+
+        // Running from the install directory puts the bundled file in the working directory, which isn't a legacy config
+        prepare_config_file(&file_path, Some(&bundled_path), Some(&bundled_path)).unwrap();
+
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "bundled\n");
+    }
+
+    #[test]
+    fn first_run_without_a_bundled_config_uses_the_built_in_one() {
+        let dir = temp_dir("built-in");
+        let file_path = dir.join("maestro").join(CONFIG_FILE_NAME);
+
+        prepare_config_file(&file_path, Some(&dir.join("missing.yaml")), Some(&dir.join(CONFIG_FILE_NAME))).unwrap();
+
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), DEFAULT_CONFIG);
+    }
+
+    const BROKEN_CONFIG: &str = "sessions: [\n";
+
+    fn good_config(hotkey: &str) -> String {
+        let mut config = Config::default();
+        config.mixer.hotkey = Some(hotkey.to_string());
+        serde_yaml::to_string(&config).unwrap()
+    }
+
+    #[test]
+    fn broken_config_falls_back_to_defaults_and_is_kept() {
+        let file_path = temp_dir("broken").join(CONFIG_FILE_NAME);
+        fs::write(&file_path, BROKEN_CONFIG).unwrap();
+
+        let broken_path = keep_broken_config(&file_path).unwrap();
+        let config = recover_config(&file_path);
+
+        assert_eq!(config.mixer.hotkey, Config::default().mixer.hotkey);
+        assert_eq!(broken_path, storage::broken_path(&file_path));
+        assert_eq!(fs::read_to_string(&broken_path).unwrap(), BROKEN_CONFIG);
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), BROKEN_CONFIG);
+        // Kept out of the rotation, where later saves would push it out
+        assert!(!storage::backup_path(&file_path, 1).exists());
+    }
+
+    #[test]
+    fn broken_config_falls_back_to_the_last_good_one() {
+        let file_path = temp_dir("last-good").join(CONFIG_FILE_NAME);
+        fs::write(storage::backup_path(&file_path, 1), good_config("Ctrl + F12")).unwrap();
+        fs::write(&file_path, BROKEN_CONFIG).unwrap();
+
+        keep_broken_config(&file_path);
+        let config = recover_config(&file_path);
+
+        assert_eq!(config.mixer.hotkey.as_deref(), Some("Ctrl + F12"));
+        assert_eq!(fs::read_to_string(storage::backup_path(&file_path, 1)).unwrap(), good_config("Ctrl + F12"));
+    }
+
+    #[test]
+    fn loading_a_current_config_leaves_the_backups_alone() {
+        let file_path = temp_dir("accept").join(CONFIG_FILE_NAME);
+        fs::write(&file_path, good_config("Ctrl + F12")).unwrap();
+
+        let (config, file_version) = read_config(&file_path).unwrap();
+        accept_config(&file_path, &config, file_version);
+
+        assert!(!storage::backup_path(&file_path, 1).exists());
+    }
+
+    #[test]
+    fn missing_config_falls_back_to_defaults() {
+        let file_path = temp_dir("missing").join(CONFIG_FILE_NAME);
+
+        assert!(keep_broken_config(&file_path).is_none());
+        let config = recover_config(&file_path);
+
+        assert_eq!(config.mixer.hotkey, Config::default().mixer.hotkey);
+        assert!(!storage::broken_path(&file_path).exists());
     }
 }
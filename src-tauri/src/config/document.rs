@@ -0,0 +1,420 @@
+use serde_yaml::{Mapping, Sequence, Value};
+
+/// Rewrite `original` so it holds `updated`, touching only the entries whose values changed.
+///
+/// Comments, blank lines and key order survive for everything that is unchanged. Nested
+/// block mappings are edited key by key and block sequences item by item; any other changed
+/// value (scalars, flow collections) is re-serialized in place. Falls back to a plain dump
+/// when the original isn't a block mapping we can edit.
+pub fn update_document(original: &str, updated: &Value) -> String {
+    let (Some(new), Ok(Value::Mapping(old))) = (updated.as_mapping(), serde_yaml::from_str::<Value>(original)) else {
+        return dump(updated);
+    };
+
+    let lines: Vec<&str> = original.lines().collect();
+
+    let mut document = edit_mapping(&lines, 0, &old, new).join("\n");
+    document.push('\n');
+    document
+}
+
+fn dump(value: &Value) -> String {
+    format!("---\n{}", serde_yaml::to_string(value).unwrap_or_default())
+}
+
+// A mapping entry, or a sequence item with an empty key
+struct Entry {
+    key: String,
+    start: usize,
+    // Exclusive end of the entry's own content; comments and blank lines after it are kept separately
+    body_end: usize,
+    end: usize,
+}
+
+fn edit_mapping(lines: &[&str], indent: usize, old: &Mapping, new: &Mapping) -> Vec<String> {
+    let entries = find_entries(lines, indent);
+    let prefix_end = entries.first().map_or(lines.len(), |entry| entry.start);
+
+    let mut output: Vec<String> = lines[..prefix_end].iter().map(|line| line.to_string()).collect();
+
+    for entry in &entries {
+        let key = Value::String(entry.key.clone());
+        let trailing = lines[entry.body_end..entry.end].iter().map(|line| line.to_string());
+
+        let Some(new_value) = new.get(&key) else {
+            // Dropped from the schema; keep the comments that followed it
+            output.extend(trailing);
+            continue;
+        };
+
+        let body = &lines[entry.start..entry.body_end];
+        match (old.get(&key), new_value) {
+            (Some(old_value), _) if old_value == new_value => {
+                output.extend(body.iter().map(|line| line.to_string()));
+            }
+            (Some(Value::Mapping(old_child)), Value::Mapping(new_child)) if is_block_key(body[0]) => {
+                let children = &body[1..];
+                match child_indent(children).filter(|child_indent| *child_indent > indent) {
+                    Some(child_indent) => {
+                        output.push(body[0].to_string());
+                        output.extend(edit_mapping(children, child_indent, old_child, new_child));
+                    }
+                    None => output.extend(serialize_entry(&key, new_value, indent)),
+                }
+            }
+            (Some(Value::Sequence(old_items)), Value::Sequence(new_items)) if is_block_key(body[0]) => {
+                let children = &body[1..];
+                let edited = item_indent(children)
+                    .filter(|item_indent| *item_indent >= indent)
+                    .and_then(|item_indent| edit_sequence(children, item_indent, old_items, new_items));
+                match edited {
+                    Some(items) => {
+                        output.push(body[0].to_string());
+                        output.extend(items);
+                    }
+                    None => output.extend(serialize_entry(&key, new_value, indent)),
+                }
+            }
+            _ => output.extend(serialize_entry(&key, new_value, indent)),
+        }
+
+        output.extend(trailing);
+    }
+
+    // Keys the file didn't have yet go after the existing ones
+    let existing: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+    for (key, value) in new {
+        if !key.as_str().is_some_and(|key| existing.contains(&key)) {
+            let insert_at = entries.last().map_or(output.len(), |_| trailing_start(&output));
+            let serialized = serialize_entry(key, value, indent);
+            output.splice(insert_at..insert_at, serialized);
+        }
+    }
+
+    output
+}
+
+// Items are matched up by position. None when the lines don't hold the old items as a block sequence.
+fn edit_sequence(lines: &[&str], indent: usize, old: &Sequence, new: &Sequence) -> Option<Vec<String>> {
+    let items = find_items(lines, indent);
+    if items.len() != old.len() {
+        return None;
+    }
+    let prefix_end = items.first().map_or(lines.len(), |item| item.start);
+
+    let mut output: Vec<String> = lines[..prefix_end].iter().map(|line| line.to_string()).collect();
+
+    for (index, item) in items.iter().enumerate() {
+        let trailing = lines[item.body_end..item.end].iter().map(|line| line.to_string());
+
+        // Removed from the end; keep the comments that followed it
+        let Some(new_value) = new.get(index) else {
+            output.extend(trailing);
+            continue;
+        };
+
+        let body = &lines[item.start..item.body_end];
+        match (&old[index], new_value) {
+            (old_value, _) if old_value == new_value => {
+                output.extend(body.iter().map(|line| line.to_string()));
+            }
+            (Value::Mapping(old_child), Value::Mapping(new_child)) => match edit_item_mapping(body, indent, old_child, new_child) {
+                Some(edited) => output.extend(edited),
+                None => output.extend(serialize_item(new_value, indent)),
+            },
+            _ => output.extend(serialize_item(new_value, indent)),
+        }
+
+        output.extend(trailing);
+    }
+
+    // Items added past the old end go after the last one
+    for value in new.iter().skip(items.len()) {
+        let insert_at = trailing_start(&output);
+        output.splice(insert_at..insert_at, serialize_item(value, indent));
+    }
+
+    Some(output)
+}
+
+// `- key: value` followed by the rest of the mapping, edited as if the dash were a space
+fn edit_item_mapping(body: &[&str], indent: usize, old: &Mapping, new: &Mapping) -> Option<Vec<String>> {
+    let first = body[0];
+    let content = first.get(indent + 2..)?;
+    if content.starts_with(' ') || content.starts_with('{') || entry_key(content, 0).is_none() {
+        return None;
+    }
+
+    let unmarked = format!("{}  {}", &first[..indent], content);
+    let mut lines = vec![unmarked.as_str()];
+    lines.extend(&body[1..]);
+
+    let mut edited = edit_mapping(&lines, indent + 2, old, new);
+    let first_entry = edited.iter_mut().find(|line| !is_blank_or_comment(line))?;
+    first_entry.replace_range(indent..indent + 1, "-");
+    Some(edited)
+}
+
+fn find_items(lines: &[&str], indent: usize) -> Vec<Entry> {
+    let is_item = |line: &str| indentation(line) == indent && (&line[indent..] == "-" || line[indent..].starts_with("- "));
+    spans(
+        lines,
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| is_item(line))
+            .map(|(index, _)| (index, String::new())),
+    )
+}
+
+fn find_entries(lines: &[&str], indent: usize) -> Vec<Entry> {
+    spans(
+        lines,
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| entry_key(line, indent).map(|key| (index, key))),
+    )
+}
+
+// Each start runs up to the next one
+fn spans(lines: &[&str], starts: impl Iterator<Item = (usize, String)>) -> Vec<Entry> {
+    let starts: Vec<(usize, String)> = starts.collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(position, (start, key))| {
+            let end = starts.get(position + 1).map_or(lines.len(), |(next, _)| *next);
+            let mut body_end = end;
+            while body_end > start + 1 && is_blank_or_comment(lines[body_end - 1]) {
+                body_end -= 1;
+            }
+            Entry {
+                key: key.clone(),
+                start: *start,
+                body_end,
+                end,
+            }
+        })
+        .collect()
+}
+
+// `key:` or `key: value` starting exactly at `indent`
+fn entry_key(line: &str, indent: usize) -> Option<String> {
+    if indentation(line) != indent {
+        return None;
+    }
+
+    let content = &line[indent..];
+    if content.starts_with('#') || content.starts_with('-') || content.starts_with("---") {
+        return None;
+    }
+
+    let colon = content.find(": ").or_else(|| content.strip_suffix(':').map(str::len))?;
+    let key = content[..colon].trim();
+    let key = key
+        .strip_prefix('"')
+        .and_then(|key| key.strip_suffix('"'))
+        .or_else(|| key.strip_prefix('\'').and_then(|key| key.strip_suffix('\'')))
+        .unwrap_or(key);
+
+    Some(key.to_string())
+}
+
+// A key whose value starts on the following lines
+fn is_block_key(line: &str) -> bool {
+    let content = match line.find(" #") {
+        Some(comment) => &line[..comment],
+        None => line,
+    };
+    content.trim_end().ends_with(':')
+}
+
+fn item_indent(lines: &[&str]) -> Option<usize> {
+    let first = lines.iter().find(|line| !is_blank_or_comment(line))?;
+    if !first.trim_start().starts_with('-') {
+        return None;
+    }
+    Some(indentation(first))
+}
+
+fn child_indent(lines: &[&str]) -> Option<usize> {
+    let first = lines.iter().find(|line| !is_blank_or_comment(line))?;
+    if first.trim_start().starts_with('-') {
+        return None;
+    }
+    Some(indentation(first))
+}
+
+fn trailing_start(output: &[String]) -> usize {
+    let mut index = output.len();
+    while index > 0 && is_blank_or_comment(&output[index - 1]) {
+        index -= 1;
+    }
+    index
+}
+
+fn serialize_entry(key: &Value, value: &Value, indent: usize) -> Vec<String> {
+    let mut entry = Mapping::new();
+    entry.insert(key.clone(), value.clone());
+
+    let padding = " ".repeat(indent);
+    serde_yaml::to_string(&entry)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| format!("{}{}", padding, line))
+        .collect()
+}
+
+fn serialize_item(value: &Value, indent: usize) -> Vec<String> {
+    let padding = " ".repeat(indent);
+    serde_yaml::to_string(&Value::Sequence(vec![value.clone()]))
+        .unwrap_or_default()
+        .lines()
+        .map(|line| format!("{}{}", padding, line))
+        .collect()
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = "---
+# Maestro config
+version: 1
+
+# Apps with their own slider
+sessions:
+  - name: master
+    keybinds: ~
+  - name: chrome # browser
+    keybinds: ~
+mixer:
+  enabled: true # show the mixer
+  hotkey: Ctrl + Shift + M
+system:
+  # start with Windows
+  autostart: true
+  show_console: false
+  theme: dark # favourite
+";
+
+    fn updated(edit: impl FnOnce(&mut Value)) -> Value {
+        let mut value: Value = serde_yaml::from_str(ORIGINAL).unwrap();
+        edit(&mut value);
+        value
+    }
+
+    #[test]
+    fn unchanged_document_round_trips_exactly() {
+        assert_eq!(update_document(ORIGINAL, &updated(|_| {})), ORIGINAL);
+    }
+
+    #[test]
+    fn nested_change_keeps_sibling_comments() {
+        let value = updated(|value| value["system"]["autostart"] = Value::Bool(false));
+
+        let document = update_document(ORIGINAL, &value);
+
+        assert!(document.contains("  # start with Windows\n  autostart: false\n"));
+        assert!(document.contains("  theme: dark # favourite\n"));
+        assert!(document.contains("  enabled: true # show the mixer\n"));
+        assert!(document.contains("# Apps with their own slider\n"));
+        assert_eq!(serde_yaml::from_str::<Value>(&document).unwrap(), value);
+    }
+
+    #[test]
+    fn added_sequence_item_goes_after_the_others() {
+        let value = updated(|value| {
+            value["sessions"]
+                .as_sequence_mut()
+                .unwrap()
+                .push(serde_yaml::from_str("{name: discord, keybinds: null}").unwrap())
+        });
+
+        let document = update_document(ORIGINAL, &value);
+
+        assert!(document.contains("# Apps with their own slider\nsessions:\n"));
+        assert!(document.contains("  - name: chrome # browser\n    keybinds: ~\n  - name: discord\n    keybinds: null\nmixer:"));
+        assert_eq!(serde_yaml::from_str::<Value>(&document).unwrap(), value);
+    }
+
+    #[test]
+    fn changed_sequence_item_keeps_the_others_and_its_comments() {
+        let value = updated(|value| value["sessions"][1]["keybinds"] = serde_yaml::from_str("[{key: F13, action: toggle_mute}]").unwrap());
+
+        let document = update_document(ORIGINAL, &value);
+
+        assert!(document.contains("  - name: master\n    keybinds: ~\n  - name: chrome # browser\n    keybinds:\n"));
+        assert_eq!(serde_yaml::from_str::<Value>(&document).unwrap(), value);
+    }
+
+    #[test]
+    fn removed_sequence_items_are_dropped() {
+        let value = updated(|value| {
+            value["sessions"].as_sequence_mut().unwrap().pop();
+        });
+
+        let document = update_document(ORIGINAL, &value);
+
+        assert!(document.contains("sessions:\n  - name: master\n    keybinds: ~\nmixer:"));
+        assert_eq!(serde_yaml::from_str::<Value>(&document).unwrap(), value);
+    }
+
+    #[test]
+    fn first_key_of_a_changed_item_keeps_its_dash() {
+        let value = updated(|value| value["sessions"][0]["name"] = "speakers".into());
+
+        let document = update_document(ORIGINAL, &value);
+
+        assert!(document.contains("sessions:\n  - name: speakers\n    keybinds: ~\n  - name: chrome # browser\n"));
+        assert_eq!(serde_yaml::from_str::<Value>(&document).unwrap(), value);
+    }
+
+    #[test]
+    fn new_keys_are_appended_and_removed_keys_dropped() {
+        let value = updated(|value| {
+            let mixer = value["mixer"].as_mapping_mut().unwrap();
+            mixer.remove("hotkey");
+            mixer.insert("position".into(), "bottom".into());
+        });
+
+        let document = update_document(ORIGINAL, &value);
+
+        assert!(document.contains("mixer:\n  enabled: true # show the mixer\n  position: bottom\nsystem:"));
+        assert_eq!(serde_yaml::from_str::<Value>(&document).unwrap(), value);
+    }
+
+    #[test]
+    fn key_order_is_preserved() {
+        let value = updated(|value| value["mixer"]["enabled"] = Value::Bool(false));
+
+        let document = update_document(ORIGINAL, &value);
+        let keys: Vec<usize> = ["version:", "sessions:", "mixer:", "system:"]
+            .iter()
+            .map(|key| document.find(key).unwrap())
+            .collect();
+
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn unreadable_original_falls_back_to_dump() {
+        let value = updated(|_| {});
+
+        for original in ["", "sessions: [", "- just\n- a list\n"] {
+            let document = update_document(original, &value);
+            assert_eq!(serde_yaml::from_str::<Value>(&document).unwrap(), value);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+use super::validation::{Diagnostic, Severity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigErrorKind {
+    Io,
+    Parse,
+    Migration,
+    Validation,
+}
+
+/// Why config.yaml could not be used, with enough context to point the user at the problem.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigError {
+    pub kind: ConfigErrorKind,
+    pub path: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+    /// Where the broken file was copied to, when it was read at startup.
+    pub backup_path: Option<String>,
+}
+
+impl ConfigError {
+    pub fn io(path: &Path, error: std::io::Error) -> Self {
+        ConfigError {
+            kind: ConfigErrorKind::Io,
+            path: path.display().to_string(),
+            line: None,
+            column: None,
+            message: error.to_string(),
+            backup_path: None,
+        }
+    }
+
+    pub fn parse(path: &Path, error: serde_yaml::Error) -> Self {
+        let location = error.location();
+        ConfigError {
+            kind: ConfigErrorKind::Parse,
+            path: path.display().to_string(),
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: error.to_string(),
+            backup_path: None,
+        }
+    }
+
+    pub fn migration(path: &Path, message: String) -> Self {
+        ConfigError {
+            kind: ConfigErrorKind::Migration,
+            path: path.display().to_string(),
+            line: None,
+            column: None,
+            message,
+            backup_path: None,
+        }
+    }
+
+    pub fn validation(path: &Path, diagnostics: &[Diagnostic]) -> Self {
+        let errors: Vec<String> = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .map(|diagnostic| format!("{}: {}", diagnostic.path, diagnostic.message))
+            .collect();
+        ConfigError {
+            kind: ConfigErrorKind::Validation,
+            path: path.display().to_string(),
+            line: None,
+            column: None,
+            message: errors.join("; "),
+            backup_path: None,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ConfigErrorKind::Io => write!(f, "Failed to read {}: {}", self.path, self.message),
+            ConfigErrorKind::Parse => write!(f, "Invalid config {}: {}", self.path, self.message),
+            ConfigErrorKind::Migration => write!(f, "Failed to upgrade config {}: {}", self.path, self.message),
+            ConfigErrorKind::Validation => write!(f, "Invalid settings in {}: {}", self.path, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
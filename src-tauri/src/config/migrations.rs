@@ -0,0 +1,159 @@
+use serde_yaml::{Mapping, Value};
+
+/// Schema version written by this build, one past the last migration.
+pub const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+const VERSION_KEY: &str = "version";
+
+type Migration = fn(&mut Mapping);
+
+// MIGRATIONS[n] upgrades a version n document to version n + 1
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+pub fn current_version() -> u32 {
+    CURRENT_VERSION
+}
+
+/// Upgrade a parsed config document in place and return the version it started at.
+pub fn migrate(document: &mut Value) -> Result<u32, String> {
+    run_migrations(document, MIGRATIONS)
+}
+
+fn run_migrations(document: &mut Value, migrations: &[Migration]) -> Result<u32, String> {
+    let mapping = document.as_mapping_mut().ok_or("expected a mapping at the top level")?;
+    let version = read_version(mapping)?;
+    let current_version = migrations.len() as u32;
+
+    if version > current_version {
+        return Err(format!(
+            "config version {} is newer than this version of Maestro supports ({})",
+            version, current_version
+        ));
+    }
+
+    for (from_version, migration) in migrations.iter().enumerate().skip(version as usize) {
+        log::info!("Migrating config from version {} to {}", from_version, from_version + 1);
+        migration(mapping);
+        mapping.insert(VERSION_KEY.into(), (from_version as u64 + 1).into());
+    }
+
+    Ok(version)
+}
+
+fn read_version(mapping: &Mapping) -> Result<u32, String> {
+    match mapping.get(VERSION_KEY) {
+        None | Some(Value::Null) => Ok(0),
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("version must be a whole number, found {:?}", value)),
+    }
+}
+
+// v0: unversioned files. Their layout is the same as v1, which only adds the version key.
+fn migrate_v0_to_v1(_config: &mut Mapping) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn document(yaml: &str) -> Value {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    const V0_CONFIG: &str = "
+sessions:
+  - name: master
+  - name: chrome
+    keybinds:
+      - key: Ctrl + F9
+        action: toggle_mute
+mixer:
+  enabled: true
+  hotkey: Ctrl + Shift + M
+system:
+  autostart: true
+  show_console: false
+  theme: dark
+";
+
+    // Stand-in steps that leave a trace, so the order they ran in shows in the document
+    fn add_first(config: &mut Mapping) {
+        config.insert("steps".into(), Value::Sequence(vec!["first".into()]));
+    }
+
+    fn add_second(config: &mut Mapping) {
+        let steps = config.entry("steps".into()).or_insert_with(|| Value::Sequence(Vec::new()));
+        steps.as_sequence_mut().unwrap().push("second".into());
+    }
+
+    const STEPS: &[Migration] = &[add_first, add_second];
+
+    #[test]
+    fn pending_steps_run_in_order_and_stamp_the_version() {
+        let mut config = document("sessions: []");
+
+        assert_eq!(run_migrations(&mut config, STEPS), Ok(0));
+        assert_eq!(config["steps"], document("[first, second]"));
+        assert_eq!(config[VERSION_KEY], Value::from(2));
+    }
+
+    #[test]
+    fn steps_already_applied_are_skipped() {
+        let mut config = document("version: 1\nsessions: []");
+
+        assert_eq!(run_migrations(&mut config, STEPS), Ok(1));
+        assert_eq!(config["steps"], document("[second]"));
+        assert_eq!(config[VERSION_KEY], Value::from(2));
+    }
+
+    #[test]
+    fn unversioned_config_is_upgraded_to_current() {
+        let mut config = document(V0_CONFIG);
+
+        assert_eq!(migrate(&mut config), Ok(0));
+        assert_eq!(config[VERSION_KEY], Value::from(CURRENT_VERSION));
+
+        let config: Config = serde_yaml::from_value(config).unwrap();
+        assert_eq!(config.version, CURRENT_VERSION);
+        assert_eq!(config.sessions.len(), 2);
+    }
+
+    #[test]
+    fn current_config_is_left_alone() {
+        let mut config = document(&format!("version: {}\n{}", CURRENT_VERSION, V0_CONFIG));
+        let original = config.clone();
+
+        assert_eq!(migrate(&mut config), Ok(CURRENT_VERSION));
+        assert_eq!(config, original);
+    }
+
+    #[test]
+    fn newer_config_is_rejected() {
+        let mut config = document(&format!("version: {}\n{}", CURRENT_VERSION + 1, V0_CONFIG));
+        let original = config.clone();
+
+        let error = migrate(&mut config).unwrap_err();
+
+        assert!(error.contains("newer"), "{}", error);
+        assert_eq!(config, original);
+    }
+
+    #[test]
+    fn non_integer_version_is_rejected() {
+        for yaml in ["version: latest", "version: 1.5", "version: -1", "version: 99999999999"] {
+            let error = migrate(&mut document(&format!("{}\nsessions: []", yaml))).unwrap_err();
+            assert!(error.contains("whole number"), "{}: {}", yaml, error);
+        }
+        assert!(migrate(&mut document("- not a mapping")).is_err());
+    }
+
+    #[test]
+    fn bundled_config_is_current() {
+        let mut config = document(include_str!("../../config.yaml"));
+
+        assert_eq!(migrate(&mut config), Ok(CURRENT_VERSION));
+        assert!(serde_yaml::from_value::<Config>(config).is_ok());
+    }
+}
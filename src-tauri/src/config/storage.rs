@@ -0,0 +1,126 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Number of previous versions of config.yaml kept as config.yaml.1.bak (newest) to config.yaml.N.bak.
+pub const BACKUP_COUNT: usize = 3;
+
+/// Replace `file_path` with `contents` without ever leaving a partially written file behind.
+///
+/// The new contents go to a temp file in the same directory which is then renamed over the
+/// original, after the current file has been rotated into `backups` backups.
+pub fn write_atomic(file_path: &Path, contents: &str, backups: usize) -> Result<(), std::io::Error> {
+    let temp_path = sibling_path(file_path, "tmp");
+    {
+        let mut temp_file = fs::File::create(&temp_path)?;
+        temp_file.write_all(contents.as_bytes())?;
+        temp_file.sync_all()?;
+    }
+
+    if file_path.exists() {
+        if let Err(e) = rotate_backups(file_path, backups) {
+            log::warn!("Failed to back up {}: {}", file_path.display(), e);
+        }
+    }
+
+    if let Err(e) = fs::rename(&temp_path, file_path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Where a config that failed to load is kept: config.yaml.broken. It stays out of the backup rotation, so saves
+/// made after recovering don't push it out.
+pub fn broken_path(file_path: &Path) -> PathBuf {
+    sibling_path(file_path, "broken")
+}
+
+/// The `index`th newest backup of `file_path`, counting from 1.
+pub fn backup_path(file_path: &Path, index: usize) -> PathBuf {
+    sibling_path(file_path, &format!("{}.bak", index))
+}
+
+/// Copy `file_path` into the newest backup, shifting older ones down and dropping any past `count`.
+/// Does nothing when the newest backup already holds the same contents.
+pub fn rotate_backups(file_path: &Path, count: usize) -> Result<(), std::io::Error> {
+    if count == 0 {
+        return Ok(());
+    }
+
+    let contents = fs::read(file_path)?;
+    if fs::read(backup_path(file_path, 1)).is_ok_and(|newest| newest == contents) {
+        return Ok(());
+    }
+
+    for index in (1..count).rev() {
+        let from = backup_path(file_path, index);
+        if from.exists() {
+            fs::rename(&from, backup_path(file_path, index + 1))?;
+        }
+    }
+
+    fs::copy(file_path, backup_path(file_path, 1))?;
+    Ok(())
+}
+
+// config.yaml -> config.yaml.<suffix>
+fn sibling_path(file_path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = file_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(suffix);
+    file_path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("maestro-storage-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn writes_new_file_without_backups() {
+        let file_path = temp_dir("new").join("config.yaml");
+
+        write_atomic(&file_path, "version: 1\n", BACKUP_COUNT).unwrap();
+
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "version: 1\n");
+        assert!(!backup_path(&file_path, 1).exists());
+        assert!(!sibling_path(&file_path, "tmp").exists());
+    }
+
+    #[test]
+    fn keeps_rolling_backups() {
+        let file_path = temp_dir("rolling").join("config.yaml");
+
+        for revision in 0..=BACKUP_COUNT + 1 {
+            write_atomic(&file_path, &format!("revision: {}\n", revision), BACKUP_COUNT).unwrap();
+        }
+
+        let newest = BACKUP_COUNT + 1;
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), format!("revision: {}\n", newest));
+        for index in 1..=BACKUP_COUNT {
+            let backup = fs::read_to_string(backup_path(&file_path, index)).unwrap();
+            assert_eq!(backup, format!("revision: {}\n", newest - index));
+        }
+        assert!(!backup_path(&file_path, BACKUP_COUNT + 1).exists());
+    }
+
+    #[test]
+    fn same_contents_are_backed_up_once() {
+        let file_path = temp_dir("same").join("config.yaml");
+        fs::write(&file_path, "revision: 1\n").unwrap();
+
+        rotate_backups(&file_path, BACKUP_COUNT).unwrap();
+        rotate_backups(&file_path, BACKUP_COUNT).unwrap();
+        write_atomic(&file_path, "revision: 2\n", BACKUP_COUNT).unwrap();
+
+        assert_eq!(fs::read_to_string(backup_path(&file_path, 1)).unwrap(), "revision: 1\n");
+        assert!(!backup_path(&file_path, 2).exists());
+    }
+}
@@ -0,0 +1,533 @@
+use serde::Serialize;
+
+use super::{Config, CurveKind, KeybindConfig};
+use crate::models::{hotkey::Hotkey, keybind_action::KeybindAction};
+use crate::services::session_matcher::SessionMatcher;
+use crate::services::volume_curve::VolumeCurve;
+
+/// Themes the UI ships with.
+pub const THEMES: &[&str] = &["light", "dark"];
+
+/// Meters never update faster than this, whatever `mixer.meter_rate` asks for.
+pub const MAX_METER_RATE: u32 = 60;
+
+// Media keys are always bound to the master volume
+const BUILT_IN_BINDINGS: &[(&str, &str)] = &[
+    ("VolumeUp", "master volume up"),
+    ("VolumeDown", "master volume down"),
+    ("VolumeMute", "master mute"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem with one field of the config. Errors block saving; warnings are informational.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic {
+            path: path.into(),
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic {
+            path: path.into(),
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+pub fn validate_config(config: &Config) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        diagnostics: Vec::new(),
+        bindings: BUILT_IN_BINDINGS
+            .iter()
+            .map(|(hotkey, owner)| (hotkey.parse().unwrap(), owner.to_string()))
+            .collect(),
+        names: Vec::new(),
+        profiles: Vec::new(),
+    };
+
+    // Profiles come first so keybinds anywhere can be checked against them
+    for (index, profile) in config.profiles.iter().enumerate() {
+        let path = format!("profiles[{}]", index);
+        let normalized = profile.name.trim().to_lowercase();
+
+        if normalized.is_empty() {
+            validator
+                .diagnostics
+                .push(Diagnostic::error(format!("{}.name", path), "Profile name is empty"));
+        } else if validator.profiles.contains(&normalized) {
+            validator.diagnostics.push(Diagnostic::error(
+                format!("{}.name", path),
+                format!("Profile {} is already defined", profile.name),
+            ));
+        } else {
+            validator.profiles.push(normalized);
+        }
+
+        for (level_index, level) in profile.sessions.iter().enumerate() {
+            if !(0..=100).contains(&level.volume) {
+                validator.diagnostics.push(Diagnostic::error(
+                    format!("{}.sessions[{}].volume", path, level_index),
+                    format!("Volume {} is out of range, expected 0-100", level.volume),
+                ));
+            }
+        }
+    }
+
+    match &config.mixer.hotkey {
+        Some(hotkey) => validator.check_hotkey(hotkey, "mixer.hotkey"),
+        None if config.mixer.enabled => validator.diagnostics.push(Diagnostic::warning(
+            "mixer.hotkey",
+            "The mixer is enabled but has no hotkey, so it can only be opened from the tray",
+        )),
+        None => {}
+    }
+
+    validator.check_step(Some(config.step.size), "step.size");
+    validator.check_step(config.step.max, "step.max");
+    if config.step.max.is_some_and(|max| max < config.step.size) {
+        validator.diagnostics.push(Diagnostic::warning(
+            "step.max",
+            format!(
+                "Max step is below the step size of {}, so holding a key won't speed it up",
+                config.step.size
+            ),
+        ));
+    }
+
+    match config.volume.curve {
+        CurveKind::Custom => {
+            if let Err(e) = VolumeCurve::custom(&config.volume.points) {
+                validator.diagnostics.push(Diagnostic::error("volume.points", e));
+            }
+        }
+        _ if !config.volume.points.is_empty() => validator
+            .diagnostics
+            .push(Diagnostic::warning("volume.points", "Points are only used by the custom curve")),
+        _ => {}
+    }
+
+    if config.mixer.meter_rate > MAX_METER_RATE {
+        validator.diagnostics.push(Diagnostic::warning(
+            "mixer.meter_rate",
+            format!("Meters update at most {} times a second", MAX_METER_RATE),
+        ));
+    }
+
+    for (index, session) in config.sessions.iter().enumerate() {
+        let path = format!("sessions[{}]", index);
+        validator.check_name(&session.name, &path, "Session");
+
+        if let Err(e) = SessionMatcher::new(session) {
+            validator.diagnostics.push(Diagnostic::error(format!("{}.title", path), e));
+        }
+
+        if session.pid.is_some() {
+            validator.diagnostics.push(Diagnostic::warning(
+                format!("{}.pid", path),
+                "Process ids change every time an app starts; exe is usually a better match",
+            ));
+        }
+
+        validator.check_step(session.step, &format!("{}.step", path));
+        validator.check_keybinds(session.keybinds.as_deref(), &path);
+    }
+
+    for (index, group) in config.groups.iter().enumerate() {
+        let path = format!("groups[{}]", index);
+        validator.check_name(&group.name, &path, "Group");
+
+        if group.members.is_empty() {
+            validator.diagnostics.push(Diagnostic::warning(
+                format!("{}.members", path),
+                format!("Group {} has no members", group.name),
+            ));
+        }
+
+        for (member_index, member) in group.members.iter().enumerate() {
+            if config.groups.iter().any(|other| other.name.eq_ignore_ascii_case(member)) {
+                validator.diagnostics.push(Diagnostic::error(
+                    format!("{}.members[{}]", path, member_index),
+                    format!("{} is a group; groups can't contain other groups", member),
+                ));
+            }
+        }
+
+        validator.check_step(group.step, &format!("{}.step", path));
+        validator.check_keybinds(group.keybinds.as_deref(), &path);
+    }
+
+    for (index, rule) in config.ducking.iter().enumerate() {
+        let path = format!("ducking[{}]", index);
+
+        if rule.trigger.trim().is_empty() {
+            validator
+                .diagnostics
+                .push(Diagnostic::error(format!("{}.trigger", path), "Ducking trigger is empty"));
+        }
+        if rule.targets.is_empty() {
+            validator.diagnostics.push(Diagnostic::warning(
+                format!("{}.targets", path),
+                format!("Ducking for {} has no targets", rule.trigger),
+            ));
+        }
+        if !(0..=100).contains(&rule.amount) {
+            validator.diagnostics.push(Diagnostic::error(
+                format!("{}.amount", path),
+                format!("Amount {} is out of range, expected 0-100", rule.amount),
+            ));
+        }
+        if !(0.0..=1.0).contains(&rule.threshold) {
+            validator.diagnostics.push(Diagnostic::error(
+                format!("{}.threshold", path),
+                format!("Threshold {} is out of range, expected 0.0-1.0", rule.threshold),
+            ));
+        }
+    }
+
+    if let Some(theme) = &config.system.theme {
+        if !THEMES.contains(&theme.as_str()) {
+            validator.diagnostics.push(Diagnostic::warning(
+                "system.theme",
+                format!("Unknown theme {}, expected one of {}", theme, THEMES.join(", ")),
+            ));
+        }
+    }
+
+    validator.diagnostics
+}
+
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+    // Every hotkey claimed so far and who claimed it
+    bindings: Vec<(Hotkey, String)>,
+    // Lowercased session and group names, which share one namespace as keybind and command targets
+    names: Vec<(String, String)>,
+    // Lowercased profile names
+    profiles: Vec<String>,
+}
+
+impl Validator {
+    fn check_name(&mut self, name: &str, path: &str, kind: &str) {
+        let normalized = name.trim().to_lowercase();
+
+        if normalized.is_empty() {
+            self.diagnostics
+                .push(Diagnostic::error(format!("{}.name", path), format!("{} name is empty", kind)));
+        } else if let Some((_, other)) = self.names.iter().find(|(existing, _)| *existing == normalized) {
+            self.diagnostics.push(Diagnostic::error(
+                format!("{}.name", path),
+                format!("{} {} is already defined by {}", kind, name, other),
+            ));
+        } else {
+            self.names.push((normalized, path.to_string()));
+        }
+    }
+
+    fn check_keybinds(&mut self, keybinds: Option<&[KeybindConfig]>, path: &str) {
+        for (index, keybind) in keybinds.unwrap_or_default().iter().enumerate() {
+            let keybind_path = format!("{}.keybinds[{}]", path, index);
+            self.check_hotkey(&keybind.key, &format!("{}.key", keybind_path));
+            self.check_step(keybind.step, &format!("{}.step", keybind_path));
+            match keybind.action.parse::<KeybindAction>() {
+                Ok(KeybindAction::Profile(name)) if !self.profiles.contains(&name.to_lowercase()) => {
+                    self.diagnostics.push(Diagnostic::warning(
+                        format!("{}.action", keybind_path),
+                        format!("There is no profile named {}", name),
+                    ));
+                }
+                Ok(action) if keybind.step.is_some() && !matches!(action, KeybindAction::VolumeUp | KeybindAction::VolumeDown) => {
+                    self.diagnostics.push(Diagnostic::warning(
+                        format!("{}.step", keybind_path),
+                        format!("{} doesn't use a step", action),
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => self.diagnostics.push(Diagnostic::error(format!("{}.action", keybind_path), e)),
+            }
+        }
+    }
+
+    fn check_step(&mut self, step: Option<i32>, path: &str) {
+        if let Some(step) = step.filter(|step| !(1..=100).contains(step)) {
+            self.diagnostics
+                .push(Diagnostic::error(path, format!("Step {} is out of range, expected 1-100", step)));
+        }
+    }
+
+    fn check_hotkey(&mut self, hotkey: &str, path: &str) {
+        let hotkey = match hotkey.parse::<Hotkey>() {
+            Ok(hotkey) => hotkey,
+            Err(e) => {
+                self.diagnostics.push(Diagnostic::error(path, format!("Invalid hotkey: {}", e)));
+                return;
+            }
+        };
+
+        if !hotkey.is_known() {
+            self.diagnostics.push(Diagnostic::warning(
+                path,
+                format!("{} isn't a key we know, the key listener may not accept it", hotkey.key),
+            ));
+        }
+
+        match self.bindings.iter().find(|(bound, _)| *bound == hotkey) {
+            Some((_, owner)) => self
+                .diagnostics
+                .push(Diagnostic::error(path, format!("{} is already bound to {}", hotkey, owner))),
+            None => self.bindings.push((hotkey, path.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DuckingConfig, GroupConfig, GroupMode, ProfileConfig, ProfileLevel, SessionConfig};
+
+    fn keybind(key: &str, action: &str) -> KeybindConfig {
+        KeybindConfig {
+            key: key.to_string(),
+            action: action.to_string(),
+            step: None,
+        }
+    }
+
+    fn paths(diagnostics: &[Diagnostic], severity: Severity) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .map(|diagnostic| diagnostic.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(validate_config(&Config::default()).is_empty());
+    }
+
+    #[test]
+    fn bundled_config_is_valid() {
+        let config: Config = serde_yaml::from_str(include_str!("../../config.yaml")).unwrap();
+        assert!(!has_errors(&validate_config(&config)));
+    }
+
+    #[test]
+    fn duplicate_and_empty_session_names_are_errors() {
+        let mut config = Config::default();
+        config.sessions.push(SessionConfig::new("Master"));
+        config.sessions.push(SessionConfig::new(" "));
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(paths(&diagnostics, Severity::Error), vec!["sessions[2].name", "sessions[3].name"]);
+    }
+
+    #[test]
+    fn bad_keybinds_are_errors() {
+        let mut config = Config::default();
+        let mut chrome = SessionConfig::new("chrome");
+        chrome.keybinds = Some(vec![keybind("Ctrl + Hyper + Q", "toggle_mute"), keybind("Ctrl + F9", "louder")]);
+        config.sessions.push(chrome);
+        config.mixer.hotkey = Some("Ctrl +".to_string());
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(
+            paths(&diagnostics, Severity::Error),
+            vec!["mixer.hotkey", "sessions[2].keybinds[0].key", "sessions[2].keybinds[1].action"]
+        );
+    }
+
+    #[test]
+    fn keybind_collisions_are_errors() {
+        let mut config = Config::default();
+        let mut chrome = SessionConfig::new("chrome");
+        chrome.keybinds = Some(vec![keybind("shift + ctrl + m", "toggle_mute"), keybind("VolumeUp", "volume_up")]);
+        let mut discord = SessionConfig::new("discord");
+        discord.keybinds = Some(vec![keybind("Ctrl + F9", "toggle_mute")]);
+        let mut spotify = SessionConfig::new("spotify");
+        spotify.keybinds = Some(vec![keybind("ctrl+f9", "set:20")]);
+        config.sessions.extend([chrome, discord, spotify]);
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(
+            paths(&diagnostics, Severity::Error),
+            vec![
+                "sessions[2].keybinds[0].key",
+                "sessions[2].keybinds[1].key",
+                "sessions[4].keybinds[0].key"
+            ]
+        );
+        assert!(diagnostics[2].message.contains("sessions[3].keybinds[0].key"));
+    }
+
+    #[test]
+    fn invalid_title_pattern_is_an_error() {
+        let mut config = Config::default();
+        let mut music = SessionConfig::new("Music");
+        music.title = Some("[spotify".to_string());
+        config.sessions.push(music);
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(paths(&diagnostics, Severity::Error), vec!["sessions[2].title"]);
+    }
+
+    #[test]
+    fn group_problems_are_reported() {
+        let mut config = Config::default();
+        let group = |name: &str, members: &[&str], keybinds| GroupConfig {
+            name: name.to_string(),
+            members: members.iter().map(|member| member.to_string()).collect(),
+            mode: GroupMode::Relative,
+            keybinds,
+            step: None,
+        };
+        config.groups = vec![
+            group("Games", &["steam"], Some(vec![keybind("Ctrl + Shift + M", "volume_up")])),
+            group("Master", &[], None),
+            group("Comms", &["discord", "games"], None),
+        ];
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(
+            paths(&diagnostics, Severity::Error),
+            vec!["groups[0].keybinds[0].key", "groups[1].name", "groups[2].members[1]"]
+        );
+        assert_eq!(paths(&diagnostics, Severity::Warning), vec!["groups[1].members"]);
+    }
+
+    #[test]
+    fn profile_problems_are_reported() {
+        let mut config = Config::default();
+        let profile = |name: &str, volume| ProfileConfig {
+            name: name.to_string(),
+            sessions: vec![ProfileLevel {
+                name: "discord".to_string(),
+                volume,
+                mute: false,
+            }],
+            fade: None,
+        };
+        config.profiles = vec![profile("Gaming", 40), profile("gaming", 40), profile("Meeting", 140)];
+        let mut chrome = SessionConfig::new("chrome");
+        chrome.keybinds = Some(vec![
+            keybind("Ctrl + F9", "profile:meeting"),
+            keybind("Ctrl + F10", "profile:Focus"),
+        ]);
+        config.sessions.push(chrome);
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(
+            paths(&diagnostics, Severity::Error),
+            vec!["profiles[1].name", "profiles[2].sessions[0].volume"]
+        );
+        assert_eq!(paths(&diagnostics, Severity::Warning), vec!["sessions[2].keybinds[1].action"]);
+    }
+
+    #[test]
+    fn ducking_problems_are_reported() {
+        let mut config = Config::default();
+        let rule = |trigger: &str, targets: &[&str], amount, threshold| DuckingConfig {
+            trigger: trigger.to_string(),
+            targets: targets.iter().map(|target| target.to_string()).collect(),
+            amount,
+            hold: 1000,
+            threshold,
+        };
+        config.ducking = vec![rule("discord", &["spotify"], 60, 0.05), rule(" ", &[], 120, 1.5)];
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(
+            paths(&diagnostics, Severity::Error),
+            vec!["ducking[1].trigger", "ducking[1].amount", "ducking[1].threshold"]
+        );
+        assert_eq!(paths(&diagnostics, Severity::Warning), vec!["ducking[1].targets"]);
+    }
+
+    #[test]
+    fn unknown_theme_key_and_missing_hotkey_are_warnings() {
+        let mut config = Config::default();
+        config.system.theme = Some("solarized".to_string());
+        config.mixer.hotkey = None;
+        config.sessions[0].pid = Some(1234);
+        config.sessions[0].keybinds = Some(vec![keybind("Ctrl + OEM_102", "toggle_mute")]);
+
+        let diagnostics = validate_config(&config);
+
+        assert!(!has_errors(&diagnostics));
+        assert_eq!(
+            paths(&diagnostics, Severity::Warning),
+            vec!["mixer.hotkey", "sessions[0].pid", "sessions[0].keybinds[0].key", "system.theme"]
+        );
+    }
+
+    #[test]
+    fn fast_meter_rate_is_a_warning() {
+        let mut config = Config::default();
+        config.mixer.meter_rate = 240;
+
+        assert_eq!(paths(&validate_config(&config), Severity::Warning), vec!["mixer.meter_rate"]);
+    }
+
+    #[test]
+    fn step_problems_are_reported() {
+        let mut config = Config::default();
+        config.step.max = Some(1);
+        config.sessions[0].step = Some(0);
+        let mut up = keybind("F13", "volume_up");
+        up.step = Some(150);
+        let mut mute = keybind("F14", "toggle_mute");
+        mute.step = Some(5);
+        config.sessions[1].keybinds = Some(vec![up, mute]);
+
+        let diagnostics = validate_config(&config);
+
+        assert_eq!(
+            paths(&diagnostics, Severity::Error),
+            vec!["sessions[0].step", "sessions[1].keybinds[0].step"]
+        );
+        assert_eq!(
+            paths(&diagnostics, Severity::Warning),
+            vec!["step.max", "sessions[1].keybinds[1].step"]
+        );
+    }
+
+    #[test]
+    fn curve_problems_are_reported() {
+        let mut config = Config::default();
+        config.volume.points = vec![[50, 20]];
+        assert_eq!(paths(&validate_config(&config), Severity::Warning), vec!["volume.points"]);
+
+        config.volume.curve = CurveKind::Custom;
+        assert!(validate_config(&config).is_empty());
+
+        config.volume.points = vec![[50, 20], [40, 60]];
+        assert_eq!(paths(&validate_config(&config), Severity::Error), vec!["volume.points"]);
+    }
+}
@@ -0,0 +1,108 @@
+use once_cell::sync::Lazy;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Editors often truncate and then write, so wait for the file to settle before reading it
+const SETTLE_DELAY: Duration = Duration::from_millis(150);
+
+static LAST_SEEN: Lazy<Mutex<Option<FileStamp>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+fn stamp(file_path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(file_path).ok()?;
+    Some(FileStamp {
+        modified: metadata.modified().ok()?,
+        len: metadata.len(),
+    })
+}
+
+/// Record the file's current state so it doesn't trigger a reload.
+pub fn mark_seen(file_path: &Path) {
+    *LAST_SEEN.lock().unwrap() = stamp(file_path);
+}
+
+/// Run `write`, our own change to the file, and mark the result as seen. The watcher waits for both, so it
+/// can't catch the new file before it is marked and reload our own save.
+pub fn write_seen<T>(file_path: &Path, write: impl FnOnce() -> T) -> T {
+    let mut last_seen = LAST_SEEN.lock().unwrap();
+    let result = write();
+    *last_seen = stamp(file_path);
+    result
+}
+
+// Whether someone else changed the file since it was last seen, and it has settled since
+fn changed(file_path: &Path) -> bool {
+    let current = stamp(file_path);
+    if current.is_none() || *LAST_SEEN.lock().unwrap() == current {
+        return false;
+    }
+
+    thread::sleep(SETTLE_DELAY);
+
+    // Checked and recorded under one lock, so a save of ours can't land in between
+    let mut last_seen = LAST_SEEN.lock().unwrap();
+    if stamp(file_path) != current {
+        return false;
+    }
+    *last_seen = current;
+    true
+}
+
+/// Poll the config file and call `on_change` whenever it is modified by someone else.
+pub fn start<F>(file_path: PathBuf, on_change: F)
+where
+    F: Fn() + Send + 'static,
+{
+    mark_seen(&file_path);
+
+    thread::Builder::new()
+        .name("config-watcher".to_string())
+        .spawn(move || loop {
+            thread::sleep(POLL_INTERVAL);
+
+            if changed(&file_path) {
+                log::info!("Config file changed on disk, reloading");
+                on_change();
+            }
+        })
+        .expect("Failed to spawn config watcher");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn own_writes_are_not_reported() {
+        let dir = std::env::temp_dir().join(format!("maestro-watcher-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("config.yaml");
+        fs::write(&file_path, "revision: 1\n").unwrap();
+        mark_seen(&file_path);
+
+        // A poll that runs while we save waits for the save to be marked, then finds nothing new
+        let poll = write_seen(&file_path, || {
+            fs::write(&file_path, "revision: 22\n").unwrap();
+            let poll = thread::spawn({
+                let file_path = file_path.clone();
+                move || changed(&file_path)
+            });
+            thread::sleep(Duration::from_millis(50));
+            poll
+        });
+        assert!(!poll.join().unwrap());
+
+        fs::write(&file_path, "revision: 333\n").unwrap();
+        assert!(changed(&file_path));
+        assert!(!changed(&file_path));
+    }
+}
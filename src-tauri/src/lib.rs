@@ -1,4 +1,4 @@
-use services::window_service;
+use services::{ducking_service, meter_service, routing_service, session_monitor, state_service, volume_service, window_service};
 use tray::system_tray;
 use utils::{logger, macro_listener};
 
@@ -7,6 +7,9 @@ mod tray {
     pub mod system_tray;
 }
 mod utils {
+    #[cfg(windows)]
+    pub mod key_state;
+    pub mod keybind_registry;
     pub mod logger;
     pub mod macro_listener;
     pub mod system_manager;
@@ -16,12 +19,38 @@ mod api {
     pub mod events;
 }
 mod services {
+    pub mod audio_backend;
+    pub mod audio_worker;
+    pub mod device_service;
+    pub mod ducking_service;
     pub mod icon_service;
+    pub mod meter_service;
+    // Stands in for the Windows backend on other platforms, where only the tests run
+    #[cfg(any(test, not(windows)))]
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub mod mock_audio_backend;
+    #[cfg(windows)]
+    pub mod policy_config;
+    #[cfg(windows)]
+    pub mod process_service;
+    pub mod profile_service;
+    pub mod routing_service;
+    pub mod session_matcher;
+    pub mod session_monitor;
+    pub mod state_service;
+    pub mod volume_curve;
     pub mod volume_service;
+    pub mod volume_step;
     pub mod window_service;
+    #[cfg(windows)]
+    pub mod windows_audio_backend;
 }
 mod models {
+    pub mod audio_device;
     pub mod audio_session;
+    pub mod hotkey;
+    pub mod keybind_action;
+    pub mod meter_update;
 }
 
 pub fn run() {
@@ -33,6 +62,9 @@ pub fn run() {
         .setup(|app| {
             let handle = app.handle();
 
+            config::init(handle);
+            config::watch(handle);
+
             // Initialize autostart plugin
             #[cfg(desktop)]
             {
@@ -43,6 +75,7 @@ pub fn run() {
                 ));
             }
 
+            #[cfg(windows)]
             utils::system_manager::handle_debug_console(handle.clone());
 
             window_service::create_overlay(handle.clone());
@@ -52,6 +85,12 @@ pub fn run() {
 
 
             macro_listener::initialize_key_listeners(handle.clone());
+            volume_service::start(handle.clone());
+            session_monitor::start(handle.clone());
+            state_service::start(handle.clone());
+            routing_service::start(handle.clone());
+            ducking_service::start(handle.clone());
+            meter_service::start(handle.clone());
 
             Ok(())
         })
@@ -66,9 +105,21 @@ pub fn run() {
             api::commands::get_session_volume,
             api::commands::set_session_volume,
             api::commands::toggle_session_mute,
+            api::commands::get_devices,
+            api::commands::get_device,
+            api::commands::set_device_volume,
+            api::commands::set_device_mute,
+            api::commands::toggle_device_mute,
+            api::commands::set_default_device,
+            api::commands::set_session_device,
+            api::commands::get_profiles,
+            api::commands::save_profile,
+            api::commands::apply_profile,
             api::commands::log,
             api::commands::get_config,
             api::commands::set_config,
+            api::commands::get_config_error,
+            api::commands::validate_config,
             api::commands::enable_autostart,
             api::commands::disable_autostart,
             api::commands::is_autostart_enabled,
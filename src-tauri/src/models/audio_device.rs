@@ -0,0 +1,31 @@
+use crate::services::audio_backend::BackendDevice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Output,
+    Input,
+}
+
+#[derive(serde::Serialize)]
+pub struct AudioDevice {
+    pub id: String,
+    pub name: String,
+    pub kind: DeviceKind,
+    pub volume: i32,
+    pub mute: bool,
+    pub is_default: bool,
+}
+
+impl AudioDevice {
+    pub fn from_device(device: &BackendDevice) -> Self {
+        AudioDevice {
+            id: device.id.clone(),
+            name: device.name.clone(),
+            kind: device.kind,
+            volume: (device.volume * 100.0).round() as i32,
+            mute: device.mute,
+            is_default: device.is_default,
+        }
+    }
+}
@@ -1,24 +1,49 @@
-use windows_volume_control::session::Session;
-
-use crate::services::icon_service;
+use crate::services::volume_curve::{self, VolumeCurve};
+use crate::services::{audio_backend::BackendSession, icon_service};
 
 #[derive(serde::Serialize)]
 pub struct AudioSession {
+    pub id: String,
     pub name: String,
+    pub pid: u32,
+    pub exe_path: Option<String>,
+    // Output the session plays on, None for groups
+    pub device_id: Option<String>,
+    // Slider position on the configured volume curve
     pub volume: i32,
+    // Level in dB, None for master whose level Windows scales itself
+    pub decibels: Option<f32>,
     pub mute: bool,
     pub icon: Option<String>,
 }
 
 impl AudioSession {
-    pub fn from_session(session: &Box<dyn Session>) -> Self {
-        unsafe {
-            AudioSession {
-                name: session.get_name().to_string(),
-                volume: (session.get_volume() * 100.0).round() as i32,
-                mute: session.get_mute(),
-                icon: icon_service::get_icon(session.get_pid()),
-            }
+    pub fn from_session(session: &BackendSession, curve: &VolumeCurve) -> Self {
+        AudioSession {
+            id: session.id.clone(),
+            name: session.name.clone(),
+            pid: session.pid,
+            exe_path: session.exe_path.clone(),
+            device_id: session.device_id.clone(),
+            volume: curve.for_session(session).to_position(session.volume),
+            decibels: (session.pid != 0).then(|| volume_curve::decibels(session.volume)),
+            mute: session.mute,
+            icon: icon_service::get_icon(session.pid),
+        }
+    }
+
+    /// A group channel, addressed by the group name.
+    pub fn from_group(name: &str, volume: f32, mute: bool, curve: &VolumeCurve) -> Self {
+        AudioSession {
+            id: name.to_string(),
+            name: name.to_string(),
+            pid: 0,
+            exe_path: None,
+            device_id: None,
+            volume: curve.to_position(volume),
+            decibels: Some(volume_curve::decibels(volume)),
+            mute,
+            icon: None,
         }
     }
 }
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::str::FromStr;
+
+const NAMED_KEYS: &[&str] = &[
+    "Space",
+    "Enter",
+    "Tab",
+    "Escape",
+    "Backspace",
+    "Delete",
+    "Insert",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "PrintScreen",
+    "Pause",
+    "CapsLock",
+    "NumLock",
+    "ScrollLock",
+    "VolumeUp",
+    "VolumeDown",
+    "VolumeMute",
+    "MediaPlayPause",
+    "MediaNextTrack",
+    "MediaPrevTrack",
+    "MediaStop",
+];
+
+// Keys of the US layout that type punctuation, named by what they type unshifted
+const PUNCTUATION_KEYS: &str = "`-=[]\\;',./";
+
+const KEY_ALIASES: &[(&str, &str)] = &[("esc", "Escape"), ("del", "Delete"), ("ins", "Insert"), ("return", "Enter")];
+
+/// A key combination such as `Ctrl + Shift + M`, as written in config.yaml.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub win: bool,
+    pub key: String,
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    fn from_str(hotkey: &str) -> Result<Self, Self::Err> {
+        let mut parsed = Hotkey {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            win: false,
+            key: String::new(),
+        };
+
+        for part in hotkey.split('+').map(str::trim) {
+            if part.is_empty() {
+                return Err(format!("'{}' has an empty key", hotkey));
+            }
+
+            let modifier = match part.to_lowercase().as_str() {
+                "ctrl" | "control" => Some(&mut parsed.ctrl),
+                "shift" => Some(&mut parsed.shift),
+                "alt" => Some(&mut parsed.alt),
+                "win" | "windows" | "super" | "meta" => Some(&mut parsed.win),
+                _ => None,
+            };
+
+            match modifier {
+                Some(true) => return Err(format!("'{}' repeats modifier {}", hotkey, part)),
+                Some(modifier) => *modifier = true,
+                None if !parsed.key.is_empty() => return Err(format!("'{}' has more than one key ({} and {})", hotkey, parsed.key, part)),
+                // Keys we don't know are still handed to the key listener, which may know them
+                None => parsed.key = canonical_key(part).unwrap_or_else(|| part.to_string()),
+            }
+        }
+
+        if parsed.key.is_empty() {
+            return Err(format!("'{}' has no key besides modifiers", hotkey));
+        }
+
+        Ok(parsed)
+    }
+}
+
+impl Hotkey {
+    /// Whether the key is one we know by name, rather than passed along as written.
+    pub fn is_known(&self) -> bool {
+        canonical_key(&self.key).is_some()
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = [(self.ctrl, "Ctrl"), (self.shift, "Shift"), (self.alt, "Alt"), (self.win, "Win")];
+        for (_, name) in modifiers.iter().filter(|(held, _)| *held) {
+            write!(f, "{} + ", name)?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+fn canonical_key(key: &str) -> Option<String> {
+    let lower = key.to_lowercase();
+
+    if key.chars().count() == 1 {
+        let c = key.chars().next()?;
+        return (c.is_ascii_alphanumeric() || PUNCTUATION_KEYS.contains(c)).then(|| c.to_ascii_uppercase().to_string());
+    }
+
+    if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return (1..=24).contains(&number).then(|| format!("F{}", number));
+    }
+
+    if let Some(digit) = lower.strip_prefix("numpad").and_then(|n| n.parse::<u8>().ok()) {
+        return (digit <= 9).then(|| format!("Numpad{}", digit));
+    }
+
+    if let Some((_, name)) = KEY_ALIASES.iter().find(|(alias, _)| *alias == lower) {
+        return Some(name.to_string());
+    }
+
+    NAMED_KEYS
+        .iter()
+        .find(|name| name.to_lowercase() == lower)
+        .map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_normalizes() {
+        let hotkey: Hotkey = "shift+ctrl + m".parse().unwrap();
+        assert_eq!(hotkey.to_string(), "Ctrl + Shift + M");
+
+        assert_eq!("VOLUMEUP".parse::<Hotkey>().unwrap().to_string(), "VolumeUp");
+        assert_eq!("Win + f13".parse::<Hotkey>().unwrap().to_string(), "Win + F13");
+        assert_eq!("Alt + esc".parse::<Hotkey>().unwrap().to_string(), "Alt + Escape");
+        assert_eq!("Ctrl+Numpad5".parse::<Hotkey>().unwrap().to_string(), "Ctrl + Numpad5");
+        assert_eq!("ctrl + /".parse::<Hotkey>().unwrap().to_string(), "Ctrl + /");
+    }
+
+    #[test]
+    fn unknown_keys_are_kept_as_written() {
+        let hotkey: Hotkey = "Ctrl + OEM_102".parse().unwrap();
+        assert_eq!(hotkey.key, "OEM_102");
+        assert!(!hotkey.is_known());
+
+        assert!(!"F25".parse::<Hotkey>().unwrap().is_known());
+        assert!("Ctrl + ;".parse::<Hotkey>().unwrap().is_known());
+    }
+
+    #[test]
+    fn rejects_malformed_hotkeys() {
+        for hotkey in [
+            "",
+            "Ctrl +",
+            "Ctrl + Shift",
+            "Ctrl + Ctrl + M",
+            "M + N",
+            "Hyper + Q",
+            "Ctrl + ; + '",
+        ] {
+            assert!(hotkey.parse::<Hotkey>().is_err(), "{} should be rejected", hotkey);
+        }
+    }
+}
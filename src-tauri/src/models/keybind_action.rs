@@ -0,0 +1,162 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// How long push-to-talk and push-to-mute hold on after the key is let go, so the end of a word isn't cut off.
+pub const DEFAULT_RELEASE_DELAY_MS: u64 = 200;
+const MAX_RELEASE_DELAY_MS: u64 = 5000;
+const PROFILE_PREFIX: &str = "profile:";
+
+/// Action a session keybind performs, parsed from `KeybindConfig::action`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeybindAction {
+    VolumeUp,
+    VolumeDown,
+    ToggleMute,
+    SetVolume(i32),
+    // These ignore the session they are bound to
+    CycleDevice,
+    ToggleMicMute,
+    // Mic unmuted or muted only while held, with a release delay in milliseconds
+    PushToTalk(u64),
+    PushToMute(u64),
+    // Apply the named profile
+    Profile(String),
+}
+
+impl FromStr for KeybindAction {
+    type Err = String;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        let original = action.trim();
+        let action = original.to_lowercase();
+
+        match action.as_str() {
+            "volume_up" => return Ok(KeybindAction::VolumeUp),
+            "volume_down" => return Ok(KeybindAction::VolumeDown),
+            "toggle_mute" => return Ok(KeybindAction::ToggleMute),
+            "cycle_device" => return Ok(KeybindAction::CycleDevice),
+            "toggle_mic_mute" => return Ok(KeybindAction::ToggleMicMute),
+            _ => {}
+        }
+
+        if let Some(volume) = action.strip_prefix("set:") {
+            let volume: i32 = volume
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{}' is not a volume, expected set:<0-100>", volume.trim()))?;
+            if !(0..=100).contains(&volume) {
+                return Err(format!("volume {} is out of range, expected set:<0-100>", volume));
+            }
+            return Ok(KeybindAction::SetVolume(volume));
+        }
+
+        // Profile names keep their case, they are matched case-insensitively anyway
+        if action.starts_with(PROFILE_PREFIX) {
+            let name = original.get(PROFILE_PREFIX.len()..).unwrap_or_default().trim();
+            if name.is_empty() {
+                return Err("profile name is missing, expected profile:<name>".to_string());
+            }
+            return Ok(KeybindAction::Profile(name.to_string()));
+        }
+
+        if let Some(delay) = release_delay(&action, "push_to_talk")? {
+            return Ok(KeybindAction::PushToTalk(delay));
+        }
+        if let Some(delay) = release_delay(&action, "push_to_mute")? {
+            return Ok(KeybindAction::PushToMute(delay));
+        }
+
+        Err(format!(
+            "unknown action '{}', expected one of volume_up, volume_down, toggle_mute, cycle_device, toggle_mic_mute, push_to_talk[:<ms>], push_to_mute[:<ms>], set:<n>, profile:<name>",
+            action
+        ))
+    }
+}
+
+// Parses `name` or `name:<ms>`, giving None when the action is something else
+fn release_delay(action: &str, name: &str) -> Result<Option<u64>, String> {
+    let Some(rest) = action.strip_prefix(name) else {
+        return Ok(None);
+    };
+    if rest.is_empty() {
+        return Ok(Some(DEFAULT_RELEASE_DELAY_MS));
+    }
+    let Some(delay) = rest.strip_prefix(':') else {
+        return Ok(None);
+    };
+
+    let delay: u64 = delay
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a delay, expected {}:<milliseconds>", delay.trim(), name))?;
+    if delay > MAX_RELEASE_DELAY_MS {
+        return Err(format!("delay {} is over {}ms", delay, MAX_RELEASE_DELAY_MS));
+    }
+    Ok(Some(delay))
+}
+
+impl fmt::Display for KeybindAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeybindAction::VolumeUp => write!(f, "volume_up"),
+            KeybindAction::VolumeDown => write!(f, "volume_down"),
+            KeybindAction::ToggleMute => write!(f, "toggle_mute"),
+            KeybindAction::SetVolume(volume) => write!(f, "set:{}", volume),
+            KeybindAction::CycleDevice => write!(f, "cycle_device"),
+            KeybindAction::ToggleMicMute => write!(f, "toggle_mic_mute"),
+            KeybindAction::PushToTalk(delay) => write!(f, "push_to_talk:{}", delay),
+            KeybindAction::PushToMute(delay) => write!(f, "push_to_mute:{}", delay),
+            KeybindAction::Profile(name) => write!(f, "{}{}", PROFILE_PREFIX, name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_actions() {
+        assert_eq!("volume_up".parse(), Ok(KeybindAction::VolumeUp));
+        assert_eq!("Volume_Down".parse(), Ok(KeybindAction::VolumeDown));
+        assert_eq!(" toggle_mute ".parse(), Ok(KeybindAction::ToggleMute));
+        assert_eq!("set:35".parse(), Ok(KeybindAction::SetVolume(35)));
+        assert_eq!("set: 0".parse(), Ok(KeybindAction::SetVolume(0)));
+        assert_eq!("cycle_device".parse(), Ok(KeybindAction::CycleDevice));
+        assert_eq!("toggle_mic_mute".parse(), Ok(KeybindAction::ToggleMicMute));
+        assert_eq!("push_to_talk".parse(), Ok(KeybindAction::PushToTalk(DEFAULT_RELEASE_DELAY_MS)));
+        assert_eq!("push_to_talk:500".parse(), Ok(KeybindAction::PushToTalk(500)));
+        assert_eq!("push_to_mute: 0".parse(), Ok(KeybindAction::PushToMute(0)));
+        assert_eq!("Profile: Late Night".parse(), Ok(KeybindAction::Profile("Late Night".to_string())));
+    }
+
+    #[test]
+    fn rejects_invalid_actions() {
+        assert!("louder".parse::<KeybindAction>().is_err());
+        assert!("set:".parse::<KeybindAction>().is_err());
+        assert!("set:loud".parse::<KeybindAction>().is_err());
+        assert!("set:101".parse::<KeybindAction>().is_err());
+        assert!("set:-1".parse::<KeybindAction>().is_err());
+        assert!("push_to_talk:soon".parse::<KeybindAction>().is_err());
+        assert!("push_to_talk:-5".parse::<KeybindAction>().is_err());
+        assert!("push_to_mute:60000".parse::<KeybindAction>().is_err());
+        assert!("push_to_talkative".parse::<KeybindAction>().is_err());
+        assert!("profile: ".parse::<KeybindAction>().is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for action in [
+            KeybindAction::VolumeUp,
+            KeybindAction::ToggleMute,
+            KeybindAction::SetVolume(80),
+            KeybindAction::CycleDevice,
+            KeybindAction::ToggleMicMute,
+            KeybindAction::PushToTalk(150),
+            KeybindAction::PushToMute(0),
+            KeybindAction::Profile("Gaming".to_string()),
+        ] {
+            assert_eq!(action.to_string().parse(), Ok(action));
+        }
+    }
+}
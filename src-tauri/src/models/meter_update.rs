@@ -0,0 +1,13 @@
+/// Peak level of one mixer channel or device, from 0.0 to 1.0.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct MeterLevel {
+    pub id: String,
+    pub peak: f32,
+}
+
+/// Payload of the meter update event. Sessions are keyed by mixer channel id, devices by endpoint id.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct MeterUpdate {
+    pub sessions: Vec<MeterLevel>,
+    pub devices: Vec<MeterLevel>,
+}
@@ -0,0 +1,60 @@
+use crate::models::audio_device::DeviceKind;
+
+/// Snapshot of a single audio session as reported by a backend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendSession {
+    /// Session instance identifier, unique per session for as long as it lives.
+    pub id: String,
+    pub name: String,
+    pub pid: u32,
+    pub exe_path: Option<String>,
+    pub title: Option<String>,
+    /// Endpoint the session plays on, when known.
+    pub device_id: Option<String>,
+    pub volume: f32,
+    pub mute: bool,
+}
+
+/// Whether a session needs its window title, which is costly to look up.
+pub type TitleFilter = Box<dyn Fn(&BackendSession) -> bool + Send>;
+
+/// Snapshot of a playback or recording device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendDevice {
+    /// Endpoint id, stable across restarts and reconnects.
+    pub id: String,
+    pub name: String,
+    pub kind: DeviceKind,
+    pub volume: f32,
+    pub mute: bool,
+    /// Default among devices of the same kind.
+    pub is_default: bool,
+}
+
+/// Everything volume_service and device_service need from the platform audio stack.
+///
+/// Sessions are addressed by their instance id and devices by endpoint id; setters return false when nothing matched.
+pub trait AudioBackend {
+    /// Called before each request so implementations can pick up new or closed sessions.
+    fn refresh(&mut self) {}
+    /// Re-read sessions and devices right away, for when a lookup missed something that may have only just started.
+    fn refresh_now(&mut self) {}
+    /// Which sessions to look window titles up for on refresh. Until this is called none are.
+    fn set_title_filter(&mut self, _needs_title: TitleFilter) {}
+
+    fn sessions(&self) -> Vec<BackendSession>;
+    fn set_volume(&mut self, session_id: &str, volume: f32) -> bool;
+    fn set_mute(&mut self, session_id: &str, mute: bool) -> bool;
+    /// Loudest sample the session played since the meter was last read, from 0.0 to 1.0.
+    fn peak(&self, session_id: &str) -> Option<f32>;
+
+    fn devices(&self) -> Vec<BackendDevice>;
+    fn set_device_volume(&mut self, device_id: &str, volume: f32) -> bool;
+    fn set_device_mute(&mut self, device_id: &str, mute: bool) -> bool;
+    /// Like `peak`, for everything playing on or recorded by the device.
+    fn device_peak(&self, device_id: &str) -> Option<f32>;
+    /// Make the device the default of its kind. For outputs, sessions and the master channel follow it afterwards.
+    fn set_default_device(&mut self, device_id: &str) -> bool;
+    /// Send the session's app to an output device, or back to the default one with None. Windows remembers this per app.
+    fn set_session_device(&mut self, session_id: &str, device_id: Option<&str>) -> bool;
+}
@@ -0,0 +1,116 @@
+use once_cell::sync::Lazy;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use super::audio_backend::AudioBackend;
+#[cfg(not(windows))]
+use super::mock_audio_backend::MockAudioBackend;
+#[cfg(windows)]
+use super::windows_audio_backend::WindowsAudioBackend;
+
+type Job = Box<dyn FnOnce(&mut dyn AudioBackend) + Send>;
+
+#[cfg(windows)]
+static AUDIO_WORKER: Lazy<AudioWorker> = Lazy::new(|| AudioWorker::spawn(WindowsAudioBackend::new));
+// There is no audio stack to talk to elsewhere, the app is only built there for its tests
+#[cfg(not(windows))]
+static AUDIO_WORKER: Lazy<AudioWorker> = Lazy::new(|| AudioWorker::spawn(MockAudioBackend::new));
+
+/// Runs a request against the shared audio backend and waits for the result.
+pub fn run<R, F>(job: F) -> R
+where
+    F: FnOnce(&mut dyn AudioBackend) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    AUDIO_WORKER.run(job)
+}
+
+/// Dedicated thread that owns an audio backend and serves requests over a channel.
+///
+/// The backend is created on the worker thread so COM is initialised once, in the
+/// apartment that will use it, rather than on every call.
+pub struct AudioWorker {
+    sender: Sender<Job>,
+}
+
+impl AudioWorker {
+    pub fn spawn<B, F>(create_backend: F) -> Self
+    where
+        B: AudioBackend + 'static,
+        F: FnOnce() -> B + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel::<Job>();
+
+        thread::Builder::new()
+            .name("audio-worker".to_string())
+            .spawn(move || {
+                let mut backend = create_backend();
+                for job in receiver {
+                    backend.refresh();
+                    // A failed request must not take the worker down with it
+                    if panic::catch_unwind(AssertUnwindSafe(|| job(&mut backend))).is_err() {
+                        log::error!("Audio worker request panicked");
+                    }
+                }
+                log::info!("Audio worker stopped");
+            })
+            .expect("Failed to spawn audio worker");
+
+        AudioWorker { sender }
+    }
+
+    pub fn run<R, F>(&self, job: F) -> R
+    where
+        F: FnOnce(&mut dyn AudioBackend) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (reply_sender, reply_receiver) = mpsc::channel();
+
+        self.sender
+            .send(Box::new(move |backend| {
+                let _ = reply_sender.send(job(backend));
+            }))
+            .expect("Audio worker is not running");
+
+        reply_receiver.recv().expect("Audio worker dropped request")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn master_volume(backend: &dyn AudioBackend) -> Option<f32> {
+        backend.sessions().into_iter().find(|session| session.id == "master").map(|session| session.volume)
+    }
+
+    #[test]
+    fn backend_state_persists_between_requests() {
+        let worker = AudioWorker::spawn(|| MockAudioBackend::new().with_session("master", 0.5, false));
+
+        worker.run(|backend| backend.set_volume("master", 0.8));
+
+        assert_eq!(worker.run(|backend| master_volume(backend)), Some(0.8));
+    }
+
+    #[test]
+    fn requests_run_on_the_worker_thread() {
+        let worker = AudioWorker::spawn(MockAudioBackend::new);
+
+        let name = worker.run(|_| thread::current().name().map(str::to_string));
+
+        assert_eq!(name.as_deref(), Some("audio-worker"));
+    }
+
+    #[test]
+    fn worker_survives_a_panicking_request() {
+        let worker = AudioWorker::spawn(|| MockAudioBackend::new().with_session("master", 0.5, false));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| worker.run(|_| -> () { panic!("boom") })));
+        assert!(result.is_err());
+
+        assert_eq!(worker.run(|backend| master_volume(backend)), Some(0.5));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::models::audio_device::{AudioDevice, DeviceKind};
+
+use super::audio_backend::{AudioBackend, BackendDevice};
+use super::audio_worker;
+
+pub fn get_devices() -> Vec<AudioDevice> {
+    let devices = audio_worker::run(|backend| backend.devices());
+    return devices.iter().map(AudioDevice::from_device).collect();
+}
+
+pub fn get_device(device_id: &str) -> Option<AudioDevice> {
+    let id = device_id.to_string();
+    let device = audio_worker::run(move |backend| find_device(backend, &id));
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+pub fn set_device_volume(device_id: &str, volume: i32) -> Option<AudioDevice> {
+    let id = device_id.to_string();
+    let device = audio_worker::run(move |backend| apply_device_volume(backend, &id, volume));
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+pub fn set_device_mute(device_id: &str, mute: bool) -> Option<AudioDevice> {
+    let id = device_id.to_string();
+    let device = audio_worker::run(move |backend| apply_device_mute(backend, &id, mute));
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+pub fn toggle_device_mute(device_id: &str) -> Option<AudioDevice> {
+    let id = device_id.to_string();
+    let device = audio_worker::run(move |backend| {
+        let mute = find_device(backend, &id)?.mute;
+        apply_device_mute(backend, &id, !mute)
+    });
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+pub fn set_default_device(device_id: &str) -> Option<AudioDevice> {
+    let id = device_id.to_string();
+    let device = audio_worker::run(move |backend| apply_default_device(backend, &id));
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+/// Mute or unmute the default input device.
+pub fn toggle_mic_mute() -> Option<AudioDevice> {
+    let device = audio_worker::run(|backend| {
+        let mic = default_device(backend, DeviceKind::Input)?;
+        apply_device_mute(backend, &mic.id, !mic.mute)
+    });
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+pub fn set_mic_mute(mute: bool) -> Option<AudioDevice> {
+    let device = audio_worker::run(move |backend| {
+        let mic = default_device(backend, DeviceKind::Input)?;
+        apply_device_mute(backend, &mic.id, mute)
+    });
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+/// Make the output after the current default the new default, wrapping around.
+pub fn cycle_default_device() -> Option<AudioDevice> {
+    let device = audio_worker::run(|backend| {
+        let next = next_device(&backend.devices())?.id.clone();
+        apply_default_device(backend, &next)
+    });
+    return device.as_ref().map(AudioDevice::from_device);
+}
+
+fn find_device(backend: &dyn AudioBackend, device_id: &str) -> Option<BackendDevice> {
+    backend.devices().into_iter().find(|device| device.id == device_id)
+}
+
+fn default_device(backend: &dyn AudioBackend, kind: DeviceKind) -> Option<BackendDevice> {
+    backend
+        .devices()
+        .into_iter()
+        .find(|device| device.kind == kind && device.is_default)
+}
+
+fn apply_device_volume(backend: &mut dyn AudioBackend, device_id: &str, volume: i32) -> Option<BackendDevice> {
+    let volume = volume.clamp(0, 100);
+    if !backend.set_device_volume(device_id, volume as f32 / 100.0) {
+        log::warn!("Set Device Volume: No Device Found: {}", device_id);
+        return None;
+    }
+
+    log::info!("Setting device {} volume -> {}", device_id, volume);
+    return find_device(backend, device_id);
+}
+
+fn apply_device_mute(backend: &mut dyn AudioBackend, device_id: &str, mute: bool) -> Option<BackendDevice> {
+    if !backend.set_device_mute(device_id, mute) {
+        log::warn!("Set Device Mute: No Device Found: {}", device_id);
+        return None;
+    }
+
+    log::info!("Setting device {} mute -> {}", device_id, mute);
+    return find_device(backend, device_id);
+}
+
+fn apply_default_device(backend: &mut dyn AudioBackend, device_id: &str) -> Option<BackendDevice> {
+    if !backend.set_default_device(device_id) {
+        log::warn!("Set Default Device: No Device Found: {}", device_id);
+        return None;
+    }
+
+    let device = find_device(backend, device_id)?;
+    log::info!("Default device -> {}", device.name);
+    return Some(device);
+}
+
+// Only one output means there is nothing to switch to
+fn next_device(devices: &[BackendDevice]) -> Option<&BackendDevice> {
+    let outputs: Vec<&BackendDevice> = devices.iter().filter(|device| device.kind == DeviceKind::Output).collect();
+    if outputs.len() < 2 {
+        return None;
+    }
+
+    let next = match outputs.iter().position(|device| device.is_default) {
+        Some(current) => (current + 1) % outputs.len(),
+        None => 0,
+    };
+    return Some(outputs[next]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn backend() -> MockAudioBackend {
+        MockAudioBackend::new()
+            .with_device("Speakers", 0.5, false, true)
+            .with_device("Headphones", 0.3, false, false)
+            .with_input("Microphone", 0.7, false, true)
+    }
+
+    #[test]
+    fn set_volume_only_touches_that_device() {
+        let mut backend = backend();
+
+        let device = apply_device_volume(&mut backend, "device-2", 80).unwrap();
+
+        assert_eq!(device.volume, 0.8);
+        assert_eq!(backend.device("device-1").unwrap().volume, 0.5);
+    }
+
+    #[test]
+    fn set_volume_clamps_to_valid_range() {
+        let mut backend = backend();
+
+        assert_eq!(apply_device_volume(&mut backend, "device-1", 140).unwrap().volume, 1.0);
+        assert_eq!(apply_device_volume(&mut backend, "device-1", -5).unwrap().volume, 0.0);
+    }
+
+    #[test]
+    fn unknown_device_returns_nothing() {
+        let mut backend = backend();
+
+        assert!(apply_device_volume(&mut backend, "device-9", 50).is_none());
+        assert!(apply_device_mute(&mut backend, "device-9", true).is_none());
+    }
+
+    #[test]
+    fn mute_is_per_device() {
+        let mut backend = backend();
+
+        assert!(apply_device_mute(&mut backend, "device-2", true).unwrap().mute);
+        assert!(!backend.device("device-1").unwrap().mute);
+    }
+
+    #[test]
+    fn set_default_moves_the_default() {
+        let mut backend = backend();
+
+        let device = apply_default_device(&mut backend, "device-2").unwrap();
+
+        assert!(device.is_default);
+        assert!(!backend.device("device-1").unwrap().is_default);
+        assert!(apply_default_device(&mut backend, "device-9").is_none());
+    }
+
+    #[test]
+    fn cycling_wraps_around() {
+        let backend = backend().with_device("HDMI", 1.0, false, false);
+        let mut devices = backend.devices();
+
+        assert_eq!(next_device(&devices).unwrap().name, "Headphones");
+
+        // Inputs are skipped
+        devices[0].is_default = false;
+        devices[3].is_default = true;
+        assert_eq!(next_device(&devices).unwrap().name, "Speakers");
+    }
+
+    #[test]
+    fn cycling_needs_another_device() {
+        let backend = MockAudioBackend::new()
+            .with_device("Speakers", 0.5, false, true)
+            .with_input("Microphone", 0.5, false, false);
+
+        assert!(next_device(&backend.devices()).is_none());
+        assert!(next_device(&[]).is_none());
+    }
+
+    #[test]
+    fn default_is_per_kind() {
+        let mut backend = backend().with_input("Headset", 0.5, false, false);
+
+        apply_default_device(&mut backend, "device-4").unwrap();
+
+        assert!(!backend.device("device-3").unwrap().is_default);
+        assert!(backend.device("device-1").unwrap().is_default);
+        assert_eq!(default_device(&backend, DeviceKind::Input).unwrap().name, "Headset");
+        assert_eq!(default_device(&backend, DeviceKind::Output).unwrap().name, "Speakers");
+    }
+}
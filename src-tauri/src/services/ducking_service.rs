@@ -0,0 +1,356 @@
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Event, Listener};
+
+use crate::api::events::{self, AppEvent};
+use crate::config::{self, Config, DuckingConfig};
+
+use super::audio_backend::{AudioBackend, BackendSession};
+use super::volume_service::{self, Targets};
+use super::{audio_worker, session_monitor};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Volumes within this of what ducking set are still ours to restore; anything further off was moved by the user
+const RESTORE_TOLERANCE: f32 = 0.005;
+
+static DUCKER: Lazy<Mutex<Ducker>> = Lazy::new(|| Mutex::new(Ducker::new(&config::get_config())));
+
+struct Rule {
+    trigger: String,
+    targets: Vec<String>,
+    // Fraction of their volume the targets keep while ducked
+    keep: f32,
+    hold: Duration,
+    threshold: f32,
+}
+
+impl Rule {
+    fn new(config: &DuckingConfig) -> Self {
+        Rule {
+            trigger: config.trigger.clone(),
+            targets: config.targets.clone(),
+            keep: 1.0 - config.amount.clamp(0, 100) as f32 / 100.0,
+            hold: Duration::from_millis(config.hold),
+            threshold: config.threshold,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Transition {
+    Duck,
+    Restore,
+}
+
+/// Whether a rule's trigger counts as playing. Quiet gaps shorter than the hold, like pauses between words,
+/// keep it ducked.
+#[derive(Default)]
+struct RuleState {
+    ducked: bool,
+    last_heard: Option<Instant>,
+}
+
+impl RuleState {
+    fn update(&mut self, rule: &Rule, peak: f32, now: Instant) -> Option<Transition> {
+        if peak >= rule.threshold {
+            self.last_heard = Some(now);
+            if self.ducked {
+                return None;
+            }
+            self.ducked = true;
+            return Some(Transition::Duck);
+        }
+
+        let held = self.last_heard.is_some_and(|heard| now.duration_since(heard) < rule.hold);
+        if !self.ducked || held {
+            return None;
+        }
+        self.ducked = false;
+        Some(Transition::Restore)
+    }
+}
+
+/// A session a rule lowered, with what to put back.
+struct Ducked {
+    session_id: String,
+    original: f32,
+    ducked: f32,
+}
+
+/// Runs the ducking rules against trigger peaks, one tick at a time.
+struct Ducker {
+    targets: Targets,
+    rules: Vec<(Rule, RuleState, Vec<Ducked>)>,
+}
+
+impl Ducker {
+    fn new(config: &Config) -> Self {
+        Ducker {
+            targets: Targets::new(config),
+            rules: config
+                .ducking
+                .iter()
+                .map(|rule| (Rule::new(rule), RuleState::default(), Vec::new()))
+                .collect(),
+        }
+    }
+
+    /// Read each trigger's peak and duck or restore its targets. Returns the sessions whose volume changed.
+    fn tick(&mut self, backend: &mut dyn AudioBackend, now: Instant) -> Vec<BackendSession> {
+        let mut changed = Vec::new();
+
+        for index in 0..self.rules.len() {
+            let (rule, state, _) = &mut self.rules[index];
+            let triggers = volume_service::resolve_sessions(backend, &rule.trigger, &self.targets);
+            let peak = triggers.iter().filter_map(|session| backend.peak(&session.id)).fold(0.0, f32::max);
+
+            match state.update(rule, peak, now) {
+                Some(Transition::Duck) => changed.extend(self.duck(backend, index, &triggers)),
+                Some(Transition::Restore) => changed.extend(self.restore(backend, index)),
+                None => {}
+            }
+        }
+
+        changed
+    }
+
+    // Master is left alone since the trigger plays through it, as are sessions another rule already lowered
+    fn duck(&mut self, backend: &mut dyn AudioBackend, index: usize, triggers: &[BackendSession]) -> Vec<BackendSession> {
+        let (rule, _, _) = &self.rules[index];
+        log::info!("Ducking for {}", rule.trigger);
+
+        let mut ducked: Vec<(BackendSession, f32)> = Vec::new();
+        for target in &rule.targets {
+            for session in volume_service::resolve_sessions(backend, target, &self.targets) {
+                let taken = triggers.iter().any(|trigger| trigger.id == session.id)
+                    || ducked.iter().any(|(other, _)| other.id == session.id)
+                    || self.is_ducked(&session.id);
+                if session.pid == 0 || taken {
+                    continue;
+                }
+                let volume = session.volume * rule.keep;
+                backend.set_volume(&session.id, volume);
+                ducked.push((session, volume));
+            }
+        }
+
+        self.rules[index].2 = ducked
+            .iter()
+            .map(|(session, volume)| Ducked {
+                session_id: session.id.clone(),
+                original: session.volume,
+                ducked: *volume,
+            })
+            .collect();
+        refreshed(backend, ducked.iter().map(|(session, _)| session.id.as_str()))
+    }
+
+    fn restore(&mut self, backend: &mut dyn AudioBackend, index: usize) -> Vec<BackendSession> {
+        let (rule, _, ducked) = &mut self.rules[index];
+        log::info!("Restoring after ducking for {}", rule.trigger);
+
+        let sessions = backend.sessions();
+        let mut restored = Vec::new();
+        for entry in ducked.drain(..) {
+            let untouched = sessions
+                .iter()
+                .find(|session| session.id == entry.session_id)
+                .is_some_and(|session| (session.volume - entry.ducked).abs() < RESTORE_TOLERANCE);
+            if untouched && backend.set_volume(&entry.session_id, entry.original) {
+                restored.push(entry.session_id);
+            }
+        }
+
+        refreshed(backend, restored.iter().map(String::as_str))
+    }
+
+    /// Put back everything still ducked, before the rules are replaced.
+    fn restore_all(&mut self, backend: &mut dyn AudioBackend) -> Vec<BackendSession> {
+        let mut changed = Vec::new();
+        for index in 0..self.rules.len() {
+            if self.rules[index].1.ducked {
+                self.rules[index].1 = RuleState::default();
+                changed.extend(self.restore(backend, index));
+            }
+        }
+        changed
+    }
+
+    fn is_ducked(&self, session_id: &str) -> bool {
+        self.rules
+            .iter()
+            .any(|(_, _, ducked)| ducked.iter().any(|entry| entry.session_id == session_id))
+    }
+}
+
+fn refreshed<'a>(backend: &dyn AudioBackend, ids: impl Iterator<Item = &'a str>) -> Vec<BackendSession> {
+    let ids: Vec<&str> = ids.collect();
+    backend
+        .sessions()
+        .into_iter()
+        .filter(|session| ids.contains(&session.id.as_str()))
+        .collect()
+}
+
+/// Watch trigger levels and duck while they play. Rules are reloaded, and anything ducked restored, when the
+/// config changes.
+pub fn start(app_handle: AppHandle) {
+    app_handle.listen(AppEvent::ConfigChange.as_str(), {
+        let app_handle = app_handle.clone();
+        move |event: Event| {
+            if let Ok(config) = serde_json::from_str::<Config>(event.payload()) {
+                let changed = run_ducker(move |ducker, backend| {
+                    let changed = ducker.restore_all(backend);
+                    *ducker = Ducker::new(&config);
+                    changed
+                });
+                emit_changes(changed, &app_handle);
+            }
+        }
+    });
+
+    thread::Builder::new()
+        .name("ducking".to_string())
+        .spawn(move || loop {
+            if !DUCKER.lock().unwrap().rules.is_empty() {
+                let changed = run_ducker(|ducker, backend| ducker.tick(backend, Instant::now()));
+                emit_changes(changed, &app_handle);
+            }
+            thread::sleep(POLL_INTERVAL);
+        })
+        .expect("Failed to start ducking");
+}
+
+// Our own volume changes are acknowledged so the session monitor doesn't report them as external. They aren't
+// remembered, the level to come back to is the one from before ducking.
+fn run_ducker<F>(job: F) -> Vec<BackendSession>
+where
+    F: FnOnce(&mut Ducker, &mut dyn AudioBackend) -> Vec<BackendSession> + Send + 'static,
+{
+    audio_worker::run(move |backend| {
+        let changed = job(&mut DUCKER.lock().unwrap(), backend);
+        session_monitor::acknowledge(&changed);
+        changed
+    })
+}
+
+fn emit_changes(changed: Vec<BackendSession>, app_handle: &AppHandle) {
+    if changed.is_empty() {
+        return;
+    }
+    let channels = audio_worker::run(move |backend| volume_service::affected_channels(backend, &changed));
+    for channel in &channels {
+        events::emit_external_volume_change_event(channel, app_handle.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SessionConfig;
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn config(amount: i32, hold: u64) -> Config {
+        let mut config = Config::default();
+        config.sessions.push(SessionConfig::new("discord"));
+        config.ducking = vec![DuckingConfig {
+            trigger: "discord".to_string(),
+            targets: vec!["spotify".to_string(), "chrome".to_string(), "master".to_string()],
+            amount,
+            hold,
+            threshold: 0.05,
+        }];
+        config
+    }
+
+    fn backend() -> MockAudioBackend {
+        MockAudioBackend::new()
+            .with_session("master", 1.0, false)
+            .with_session("discord", 0.8, false)
+            .with_session("spotify", 0.6, false)
+            .with_session("chrome", 0.4, false)
+            .with_session("steam", 0.5, false)
+    }
+
+    fn volume(backend: &MockAudioBackend, name: &str) -> f32 {
+        (backend.named(name).unwrap().volume * 100.0).round() / 100.0
+    }
+
+    // Feeds discord's peaks one tick at a time, 50ms apart, and returns the spotify volume after each
+    fn simulate(ducker: &mut Ducker, backend: &mut MockAudioBackend, peaks: &[f32]) -> Vec<f32> {
+        let discord = backend.named("discord").unwrap().id.clone();
+        let start = Instant::now();
+
+        peaks
+            .iter()
+            .enumerate()
+            .map(|(tick, peak)| {
+                backend.set_peak(&discord, *peak);
+                ducker.tick(backend, start + POLL_INTERVAL * tick as u32);
+                volume(backend, "spotify")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ducks_while_trigger_plays_and_restores_after_hold() {
+        let mut backend = backend();
+        let mut ducker = Ducker::new(&config(50, 100));
+
+        let spotify = simulate(&mut ducker, &mut backend, &[0.0, 0.3, 0.0, 0.2, 0.0, 0.0, 0.0, 0.0]);
+
+        // The quiet tick between words is shorter than the hold, the two after the last one are not
+        assert_eq!(spotify, vec![0.6, 0.3, 0.3, 0.3, 0.3, 0.6, 0.6, 0.6]);
+    }
+
+    #[test]
+    fn leaves_trigger_master_and_other_sessions_alone() {
+        let mut backend = backend();
+        let mut ducker = Ducker::new(&config(75, 100));
+
+        simulate(&mut ducker, &mut backend, &[0.5]);
+
+        assert_eq!(volume(&backend, "chrome"), 0.1);
+        assert_eq!(volume(&backend, "discord"), 0.8);
+        assert_eq!(volume(&backend, "master"), 1.0);
+        assert_eq!(volume(&backend, "steam"), 0.5);
+    }
+
+    #[test]
+    fn quiet_trigger_never_ducks() {
+        let mut backend = backend();
+        let mut ducker = Ducker::new(&config(50, 100));
+
+        assert_eq!(simulate(&mut ducker, &mut backend, &[0.01, 0.04, 0.0]), vec![0.6, 0.6, 0.6]);
+    }
+
+    #[test]
+    fn volume_moved_while_ducked_is_kept() {
+        let mut backend = backend();
+        let mut ducker = Ducker::new(&config(50, 0));
+        simulate(&mut ducker, &mut backend, &[0.5]);
+
+        let spotify = backend.named("spotify").unwrap().id.clone();
+        backend.set_volume(&spotify, 0.9);
+        simulate(&mut ducker, &mut backend, &[0.0]);
+
+        assert_eq!(volume(&backend, "spotify"), 0.9);
+        assert_eq!(volume(&backend, "chrome"), 0.4);
+    }
+
+    #[test]
+    fn restore_all_puts_back_ducked_sessions() {
+        let mut backend = backend();
+        let mut ducker = Ducker::new(&config(50, 1000));
+        simulate(&mut ducker, &mut backend, &[0.5]);
+
+        let changed = ducker.restore_all(&mut backend);
+
+        assert_eq!(changed.len(), 2);
+        assert_eq!(volume(&backend, "spotify"), 0.6);
+        assert!(ducker.restore_all(&mut backend).is_empty());
+    }
+}
@@ -1,6 +1,13 @@
+#[cfg(not(windows))]
+pub fn get_icon(_pid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(windows)]
 pub fn get_icon(pid: u32) -> Option<String> {
     let icon     = if pid != 0 {
-        Some(windows_icons::get_icon_base64_by_process_id(pid).unwrap())
+        // The process may already be gone, e.g. for a session that just closed
+        windows_icons::get_icon_base64_by_process_id(pid).ok()
     } else {
         None
     };
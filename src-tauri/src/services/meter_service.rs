@@ -0,0 +1,123 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Event, Listener};
+
+use crate::api::events::{self, AppEvent};
+use crate::config::{self, MAX_METER_RATE};
+use crate::models::meter_update::{MeterLevel, MeterUpdate};
+
+use super::audio_backend::AudioBackend;
+use super::audio_worker;
+use super::volume_service::{self, Targets};
+
+// Bumped every time the mixer is shown or hidden, which stops the sampler started before
+static METER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Sample peak levels while the mixer is visible and stream them to it.
+pub fn start(app_handle: AppHandle) {
+    app_handle.listen(AppEvent::MixerVisibilityChange.as_str(), {
+        let app_handle = app_handle.clone();
+        move |event: Event| {
+            let visible = serde_json::from_str::<bool>(event.payload()).unwrap_or(false);
+            let generation = METER_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+            if visible {
+                start_sampling(generation, app_handle.clone());
+            }
+        }
+    });
+}
+
+fn start_sampling(generation: u64, app_handle: AppHandle) {
+    let rate = config::get_config().mixer.meter_rate.min(MAX_METER_RATE);
+    if rate == 0 {
+        return;
+    }
+    let interval = Duration::from_millis(1000 / rate as u64);
+    // Config changes made while the mixer is open apply the next time it opens
+    let targets = Targets::load();
+
+    thread::spawn(move || {
+        let mut last = MeterUpdate::default();
+
+        while METER_GENERATION.load(Ordering::SeqCst) == generation {
+            let targets = targets.clone();
+            let update = audio_worker::run(move |backend| sample(backend, &targets));
+
+            if update != last {
+                events::emit_meter_update_event(&update, app_handle.clone());
+                last = update;
+            }
+            thread::sleep(interval);
+        }
+    });
+}
+
+fn sample(backend: &dyn AudioBackend, targets: &Targets) -> MeterUpdate {
+    let sessions = volume_service::channel_peaks(backend, targets)
+        .into_iter()
+        .map(|(id, peak)| level(id, peak))
+        .collect();
+    let devices = backend
+        .devices()
+        .into_iter()
+        .filter_map(|device| Some(level(device.id.clone(), backend.device_peak(&device.id)?)))
+        .collect();
+
+    MeterUpdate { sessions, devices }
+}
+
+// Rounded to what a meter can show, so a quiet mixer doesn't send the same picture over and over
+fn level(id: String, peak: f32) -> MeterLevel {
+    MeterLevel {
+        id,
+        peak: (peak.clamp(0.0, 1.0) * 100.0).round() / 100.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn backend() -> MockAudioBackend {
+        MockAudioBackend::new()
+            .with_device("Speakers", 0.5, false, true)
+            .with_input("Microphone", 0.5, false, true)
+            .with_session("master", 0.5, false)
+            .with_session("discord", 0.5, false)
+    }
+
+    #[test]
+    fn samples_sessions_and_devices() {
+        let mut backend = backend();
+        let discord = backend.named("discord").unwrap().id.clone();
+        backend.set_peak(&discord, 0.4217);
+        backend.set_peak("device-2", 0.9);
+
+        let update = sample(&backend, &Targets::new(&Config::default()));
+
+        assert_eq!(update.sessions, vec![level("master".to_string(), 0.0), level(discord, 0.42)]);
+        assert_eq!(
+            update.devices,
+            vec![level("device-1".to_string(), 0.0), level("device-2".to_string(), 0.9)]
+        );
+    }
+
+    #[test]
+    fn changes_below_a_percent_are_not_an_update() {
+        let mut backend = backend();
+        let targets = Targets::new(&Config::default());
+        let discord = backend.named("discord").unwrap().id.clone();
+
+        backend.set_peak(&discord, 0.301);
+        let first = sample(&backend, &targets);
+        backend.set_peak(&discord, 0.304);
+        assert_eq!(sample(&backend, &targets), first);
+
+        backend.set_peak(&discord, 0.32);
+        assert_ne!(sample(&backend, &targets), first);
+    }
+}
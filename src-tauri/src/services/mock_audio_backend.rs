@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use crate::models::audio_device::DeviceKind;
+
+use super::audio_backend::{AudioBackend, BackendDevice, BackendSession};
+
+/// Deterministic in-memory backend used by tests.
+#[derive(Default)]
+pub struct MockAudioBackend {
+    sessions: Vec<BackendSession>,
+    devices: Vec<BackendDevice>,
+    peaks: HashMap<String, f32>,
+    // Sessions that only show up once the backend is refreshed
+    pending: Vec<BackendSession>,
+    next_pid: u32,
+}
+
+impl MockAudioBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_session(mut self, name: &str, volume: f32, mute: bool) -> Self {
+        self.add_session(name, volume, mute);
+        self
+    }
+
+    pub fn with_device(self, name: &str, volume: f32, mute: bool, is_default: bool) -> Self {
+        self.with_endpoint(DeviceKind::Output, name, volume, mute, is_default)
+    }
+
+    pub fn with_input(self, name: &str, volume: f32, mute: bool, is_default: bool) -> Self {
+        self.with_endpoint(DeviceKind::Input, name, volume, mute, is_default)
+    }
+
+    fn with_endpoint(mut self, kind: DeviceKind, name: &str, volume: f32, mute: bool, is_default: bool) -> Self {
+        self.devices.push(BackendDevice {
+            id: format!("device-{}", self.devices.len() + 1),
+            name: name.to_string(),
+            kind,
+            volume,
+            mute,
+            is_default,
+        });
+        self
+    }
+
+    pub fn device(&self, device_id: &str) -> Option<&BackendDevice> {
+        self.devices.iter().find(|device| device.id == device_id)
+    }
+
+    /// Adds a session for `<name>.exe` and returns its id. "master" stands in for the endpoint, like the real backend.
+    pub fn add_session(&mut self, name: &str, volume: f32, mute: bool) -> String {
+        if name == "master" {
+            self.sessions.push(BackendSession {
+                id: name.to_string(),
+                name: name.to_string(),
+                pid: 0,
+                exe_path: None,
+                title: None,
+                device_id: None,
+                volume,
+                mute,
+            });
+            return name.to_string();
+        }
+
+        self.add_process(name, &format!("C:\\Program Files\\{}\\{}.exe", name, name), volume, mute)
+    }
+
+    pub fn add_process(&mut self, name: &str, exe_path: &str, volume: f32, mute: bool) -> String {
+        self.next_pid += 1;
+        let id = format!("{}|{}", exe_path, self.next_pid);
+        self.sessions.push(BackendSession {
+            id: id.clone(),
+            name: name.to_string(),
+            pid: self.next_pid,
+            exe_path: Some(exe_path.to_string()),
+            title: None,
+            device_id: None,
+            volume,
+            mute,
+        });
+        id
+    }
+
+    /// Like `add_session`, for a session that started after the last refresh, so only `refresh_now` finds it.
+    pub fn add_pending_session(&mut self, name: &str, volume: f32, mute: bool) -> String {
+        let id = self.add_session(name, volume, mute);
+        self.pending.extend(self.sessions.pop());
+        id
+    }
+
+    pub fn set_title(&mut self, session_id: &str, title: &str) {
+        if let Some(session) = self.session_mut(session_id) {
+            session.title = Some(title.to_string());
+        }
+    }
+
+    pub fn remove_session(&mut self, session_id: &str) {
+        self.sessions.retain(|session| session.id != session_id);
+    }
+
+    pub fn session(&self, session_id: &str) -> Option<&BackendSession> {
+        self.sessions.iter().find(|session| session.id == session_id)
+    }
+
+    /// Sessions and devices are silent until given a peak.
+    pub fn set_peak(&mut self, id: &str, peak: f32) {
+        self.peaks.insert(id.to_string(), peak);
+    }
+
+    /// First session with the given name, for tests that don't care which instance they get.
+    pub fn named(&self, name: &str) -> Option<&BackendSession> {
+        self.sessions.iter().find(|session| session.name == name)
+    }
+
+    fn session_mut(&mut self, session_id: &str) -> Option<&mut BackendSession> {
+        self.sessions.iter_mut().find(|session| session.id == session_id)
+    }
+}
+
+impl AudioBackend for MockAudioBackend {
+    fn refresh_now(&mut self) {
+        self.sessions.append(&mut self.pending);
+    }
+
+    fn sessions(&self) -> Vec<BackendSession> {
+        self.sessions.clone()
+    }
+
+    fn set_volume(&mut self, session_id: &str, volume: f32) -> bool {
+        match self.session_mut(session_id) {
+            Some(session) => {
+                session.volume = volume;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn set_mute(&mut self, session_id: &str, mute: bool) -> bool {
+        match self.session_mut(session_id) {
+            Some(session) => {
+                session.mute = mute;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn peak(&self, session_id: &str) -> Option<f32> {
+        self.session(session_id)?;
+        Some(self.peaks.get(session_id).copied().unwrap_or_default())
+    }
+
+    fn devices(&self) -> Vec<BackendDevice> {
+        self.devices.clone()
+    }
+
+    fn set_device_volume(&mut self, device_id: &str, volume: f32) -> bool {
+        match self.devices.iter_mut().find(|device| device.id == device_id) {
+            Some(device) => {
+                device.volume = volume;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn set_device_mute(&mut self, device_id: &str, mute: bool) -> bool {
+        match self.devices.iter_mut().find(|device| device.id == device_id) {
+            Some(device) => {
+                device.mute = mute;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn device_peak(&self, device_id: &str) -> Option<f32> {
+        self.device(device_id)?;
+        Some(self.peaks.get(device_id).copied().unwrap_or_default())
+    }
+
+    fn set_default_device(&mut self, device_id: &str) -> bool {
+        let Some(kind) = self.device(device_id).map(|device| device.kind) else {
+            return false;
+        };
+
+        for device in self.devices.iter_mut().filter(|device| device.kind == kind) {
+            device.is_default = device.id == device_id;
+        }
+        true
+    }
+
+    fn set_session_device(&mut self, session_id: &str, device_id: Option<&str>) -> bool {
+        if let Some(device_id) = device_id {
+            if !self
+                .devices
+                .iter()
+                .any(|device| device.id == device_id && device.kind == DeviceKind::Output)
+            {
+                return false;
+            }
+        }
+
+        match self.session_mut(session_id) {
+            Some(session) if session.pid != 0 => {
+                session.device_id = device_id.map(str::to_string);
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::ffi::c_void;
+
+use windows::core::{IInspectable_Vtbl, IUnknown, IUnknown_Vtbl, Interface, GUID, HRESULT, HSTRING, PCWSTR};
+use windows::Win32::Media::Audio::{eCommunications, eConsole, eMultimedia, eRender, EDataFlow, ERole};
+use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+use windows::Win32::System::WinRT::RoGetActivationFactory;
+
+// Windows has no public API for changing the default endpoint. IPolicyConfig is the undocumented
+// interface the Sound control panel uses, stable since Windows 7.
+const CLSID_POLICY_CONFIG_CLIENT: GUID = GUID::from_u128(0x870af99c_171d_4f9e_af0d_e63df40c2bc9);
+
+#[repr(transparent)]
+#[derive(Clone)]
+struct IPolicyConfig(IUnknown);
+
+unsafe impl Interface for IPolicyConfig {
+    type Vtable = IPolicyConfigVtbl;
+    const IID: GUID = GUID::from_u128(0xf8679f50_850a_41cf_9c72_430f290290c8);
+}
+
+#[repr(C)]
+struct IPolicyConfigVtbl {
+    base: IUnknown_Vtbl,
+    // GetMixFormat through SetPropertyValue, which we never call
+    _unused: [*const c_void; 10],
+    set_default_endpoint: unsafe extern "system" fn(*mut c_void, PCWSTR, ERole) -> HRESULT,
+    _set_endpoint_visibility: *const c_void,
+}
+
+/// Make `device_id` the default endpoint for every role, like picking it in the Sound control panel.
+/// COM must already be initialized on the calling thread.
+pub fn set_default_endpoint(device_id: &str) -> windows::core::Result<()> {
+    let id = HSTRING::from(device_id);
+
+    unsafe {
+        let policy: IPolicyConfig = CoCreateInstance(&CLSID_POLICY_CONFIG_CLIENT, None, CLSCTX_ALL)?;
+        for role in [eConsole, eMultimedia, eCommunications] {
+            (policy.vtable().set_default_endpoint)(policy.as_raw(), PCWSTR(id.as_ptr()), role).ok()?;
+        }
+    }
+
+    Ok(())
+}
+
+// Per-app endpoints go through the factory behind the "App volume and device preferences" page, which is
+// just as undocumented. Its IID changed in Windows 10 21H2, the layout did not.
+const AUDIO_POLICY_CONFIG_CLASS: &str = "Windows.Media.Internal.AudioPolicyConfig";
+
+// Endpoint ids are passed as device interface paths: prefix, endpoint id, render interface class
+const MMDEVAPI_TOKEN: &str = r"\\?\SWD#MMDEVAPI#";
+const DEVINTERFACE_AUDIO_RENDER: &str = "#{e6327cad-dcec-4949-ae8a-991e976a79d2}";
+
+#[repr(transparent)]
+#[derive(Clone)]
+struct IAudioPolicyConfigFactory(IUnknown);
+
+unsafe impl Interface for IAudioPolicyConfigFactory {
+    type Vtable = IAudioPolicyConfigFactoryVtbl;
+    const IID: GUID = GUID::from_u128(0xab3d4648_e242_459f_b02f_541c70306324);
+}
+
+#[repr(transparent)]
+#[derive(Clone)]
+struct IAudioPolicyConfigFactoryLegacy(IUnknown);
+
+unsafe impl Interface for IAudioPolicyConfigFactoryLegacy {
+    type Vtable = IAudioPolicyConfigFactoryVtbl;
+    const IID: GUID = GUID::from_u128(0x2a59116d_6c4f_45e0_a74f_707e3fef9258);
+}
+
+#[repr(C)]
+struct IAudioPolicyConfigFactoryVtbl {
+    base: IInspectable_Vtbl,
+    // Volume groups, ringer and chat app methods we never call
+    _unused: [*const c_void; 19],
+    set_persisted_default_audio_endpoint: unsafe extern "system" fn(*mut c_void, u32, EDataFlow, ERole, *mut c_void) -> HRESULT,
+    _get_persisted_default_audio_endpoint: *const c_void,
+    _clear_all_persisted_application_default_endpoints: *const c_void,
+}
+
+/// Send the audio of process `pid` to `device_id`, or back to the default output with None.
+/// Windows keeps the choice for the app across restarts.
+pub fn set_app_endpoint(pid: u32, device_id: Option<&str>) -> windows::core::Result<()> {
+    let class = HSTRING::from(AUDIO_POLICY_CONFIG_CLASS);
+    // An empty string clears the app's preference
+    let device = match device_id {
+        Some(device_id) => HSTRING::from(format!("{}{}{}", MMDEVAPI_TOKEN, device_id, DEVINTERFACE_AUDIO_RENDER)),
+        None => HSTRING::new(),
+    };
+
+    unsafe {
+        let factory = match RoGetActivationFactory::<IAudioPolicyConfigFactory>(&class) {
+            Ok(factory) => factory.0,
+            Err(_) => RoGetActivationFactory::<IAudioPolicyConfigFactoryLegacy>(&class)?.0,
+        };
+        let vtable = &**(factory.as_raw() as *const *const IAudioPolicyConfigFactoryVtbl);
+
+        for role in [eConsole, eMultimedia] {
+            let device: *mut c_void = std::mem::transmute_copy(&device);
+            (vtable.set_persisted_default_audio_endpoint)(factory.as_raw(), pid, eRender, role, device).ok()?;
+        }
+    }
+
+    Ok(())
+}
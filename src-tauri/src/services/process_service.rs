@@ -0,0 +1,56 @@
+use windows::core::{BOOL, PWSTR};
+use windows::Win32::Foundation::{CloseHandle, HWND, LPARAM};
+use windows::Win32::System::Threading::{OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION};
+use windows::Win32::UI::WindowsAndMessaging::{EnumWindows, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible};
+
+/// A visible top-level window and the process that owns it.
+pub struct ProcessWindow {
+    pub pid: u32,
+    pub title: String,
+}
+
+/// Full path of the executable running as `pid`, if the process is still alive and we may query it.
+pub fn get_exe_path(pid: u32) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut length);
+        let _ = CloseHandle(process);
+
+        result.ok()?;
+        Some(String::from_utf16_lossy(&buffer[..length as usize]))
+    }
+}
+
+/// Visible, titled top-level windows in z-order, so an app's frontmost window comes first.
+pub fn get_windows() -> Vec<ProcessWindow> {
+    let mut windows: Vec<(u32, String)> = Vec::new();
+    unsafe {
+        let _ = EnumWindows(Some(collect_window), LPARAM(&mut windows as *mut Vec<(u32, String)> as isize));
+    }
+
+    windows.into_iter().map(|(pid, title)| ProcessWindow { pid, title }).collect()
+}
+
+unsafe extern "system" fn collect_window(window: HWND, lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam.0 as *mut Vec<(u32, String)>);
+
+    if IsWindowVisible(window).as_bool() {
+        let mut buffer = [0u16; 512];
+        let length = GetWindowTextW(window, &mut buffer);
+        if length > 0 {
+            let mut pid = 0;
+            GetWindowThreadProcessId(window, Some(&mut pid));
+            windows.push((pid, String::from_utf16_lossy(&buffer[..length as usize])));
+        }
+    }
+
+    // Keep enumerating
+    true.into()
+}
@@ -0,0 +1,214 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use tauri::AppHandle;
+
+use crate::api::events;
+use crate::config::{self, Config, Diagnostic, ProfileConfig, ProfileLevel};
+use crate::models::audio_session::AudioSession;
+
+use super::volume_service;
+
+const FADE_INTERVAL_MS: u64 = 20;
+
+// Bumped whenever a profile is applied, so a fade still running gives way to the newer one
+static FADE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn get_profiles() -> Vec<ProfileConfig> {
+    config::get_config().profiles
+}
+
+/// Save the current level of every mixer channel as profile `name`, replacing a profile of the same name
+/// but keeping its fade.
+pub fn save_profile(name: &str, app_handle: &AppHandle) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+    let name = name.trim();
+    let sessions = snapshot(&volume_service::get_all_sessions());
+    let mut config = config::get_config();
+
+    match config.profiles.iter_mut().find(|profile| profile.name.eq_ignore_ascii_case(name)) {
+        Some(profile) => profile.sessions = sessions,
+        None => config.profiles.push(ProfileConfig {
+            name: name.to_string(),
+            sessions,
+            fade: None,
+        }),
+    }
+
+    log::info!("Saving profile {}", name);
+    config::set_config(config, app_handle)
+}
+
+/// Apply profile `name`, fading over its `fade` when it has one. Returns false if there is no such profile.
+pub fn apply_profile(name: &str, app_handle: AppHandle) -> bool {
+    let Some(profile) = find_profile(&config::get_config(), name).cloned() else {
+        log::warn!("Apply Profile: No Profile Found: {}", name);
+        return false;
+    };
+
+    let generation = FADE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    log::info!("Applying profile {}", profile.name);
+
+    match profile.fade.filter(|fade| *fade > 0) {
+        Some(fade) => {
+            thread::spawn(move || fade_to(&profile.sessions, fade, generation, app_handle));
+        }
+        None => {
+            for level in &profile.sessions {
+                apply_level(level, app_handle.clone());
+            }
+        }
+    }
+    true
+}
+
+fn find_profile<'a>(config: &'a Config, name: &str) -> Option<&'a ProfileConfig> {
+    config
+        .profiles
+        .iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(name.trim()))
+}
+
+// Sessions of the same app show up once per process, the first one speaks for all of them
+fn snapshot(sessions: &[AudioSession]) -> Vec<ProfileLevel> {
+    let mut levels: Vec<ProfileLevel> = Vec::new();
+
+    for session in sessions {
+        if levels.iter().any(|level| level.name.eq_ignore_ascii_case(&session.name)) {
+            continue;
+        }
+        levels.push(ProfileLevel {
+            name: session.name.clone(),
+            volume: session.volume,
+            mute: session.mute,
+        });
+    }
+
+    levels
+}
+
+fn apply_level(level: &ProfileLevel, app_handle: AppHandle) {
+    volume_service::set_session_mute(&level.name, level.mute);
+    if let Some(session) = volume_service::set_session_volume(&level.name, level.volume) {
+        events::emit_external_volume_change_event(&session, app_handle);
+    }
+}
+
+// Unmuting happens up front so the fade in is heard, muting only once the fade out is done
+fn fade_to(levels: &[ProfileLevel], fade: u64, generation: u64, app_handle: AppHandle) {
+    let starts: Vec<(&ProfileLevel, i32)> = levels
+        .iter()
+        .filter_map(|level| Some((level, volume_service::get_session(&level.name)?.volume)))
+        .collect();
+
+    for (level, _) in starts.iter().filter(|(level, _)| !level.mute) {
+        volume_service::set_session_mute(&level.name, false);
+    }
+
+    let steps = fade_steps(fade);
+    for step in 1..=steps {
+        thread::sleep(Duration::from_millis(FADE_INTERVAL_MS));
+        if FADE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+
+        for (level, start) in &starts {
+            let volume = fade_volume(*start, level.volume, step, steps);
+            if let Some(session) = volume_service::set_session_volume(&level.name, volume) {
+                events::emit_external_volume_change_event(&session, app_handle.clone());
+            }
+        }
+    }
+
+    for (level, _) in starts.iter().filter(|(level, _)| level.mute) {
+        volume_service::set_session_mute(&level.name, true);
+    }
+    for level in levels {
+        if let Some(session) = volume_service::get_session(&level.name) {
+            events::emit_external_volume_change_event(&session, app_handle.clone());
+        }
+    }
+}
+
+fn fade_steps(fade: u64) -> u64 {
+    (fade / FADE_INTERVAL_MS).max(1)
+}
+
+/// Volume `step` of `steps` on the way from `from` to `to`, landing exactly on `to` at the last step.
+fn fade_volume(from: i32, to: i32, step: u64, steps: u64) -> i32 {
+    let progress = step.min(steps) as f32 / steps as f32;
+    from + ((to - from) as f32 * progress).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, volume: i32, mute: bool) -> AudioSession {
+        AudioSession {
+            id: format!("{}|1", name),
+            name: name.to_string(),
+            pid: 1,
+            exe_path: None,
+            device_id: None,
+            volume,
+            decibels: None,
+            mute,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn snapshot_keeps_one_level_per_name() {
+        let sessions = [
+            session("master", 80, false),
+            session("chrome", 40, false),
+            session("Chrome", 10, false),
+            session("discord", 25, true),
+        ];
+
+        let levels = snapshot(&sessions);
+
+        assert_eq!(
+            levels
+                .iter()
+                .map(|level| (level.name.as_str(), level.volume, level.mute))
+                .collect::<Vec<_>>(),
+            vec![("master", 80, false), ("chrome", 40, false), ("discord", 25, true)]
+        );
+    }
+
+    #[test]
+    fn profiles_are_found_by_name_ignoring_case() {
+        let config = Config {
+            profiles: vec![ProfileConfig {
+                name: "Gaming".to_string(),
+                sessions: Vec::new(),
+                fade: None,
+            }],
+            ..Default::default()
+        };
+
+        assert!(find_profile(&config, " gaming").is_some());
+        assert!(find_profile(&config, "Meeting").is_none());
+    }
+
+    #[test]
+    fn fade_moves_evenly_and_lands_on_target() {
+        let steps = fade_steps(100);
+
+        assert_eq!(steps, 5);
+        assert_eq!(
+            (1..=steps).map(|step| fade_volume(20, 70, step, steps)).collect::<Vec<_>>(),
+            vec![30, 40, 50, 60, 70]
+        );
+        assert_eq!(fade_volume(70, 20, 1, steps), 60);
+        assert_eq!(fade_volume(33, 34, steps, steps), 34);
+    }
+
+    #[test]
+    fn short_fades_take_one_step() {
+        assert_eq!(fade_steps(5), 1);
+        assert_eq!(fade_volume(0, 100, 1, fade_steps(5)), 100);
+    }
+}
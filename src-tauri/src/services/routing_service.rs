@@ -0,0 +1,182 @@
+use tauri::{AppHandle, Event, Listener};
+
+use crate::api::events::AppEvent;
+use crate::config::{self, Config};
+use crate::models::audio_device::DeviceKind;
+
+use super::audio_backend::{AudioBackend, BackendDevice, BackendSession};
+use super::audio_worker;
+use super::session_matcher::{self, SessionMatcher};
+
+/// A session entry with an `output_device`.
+struct Route {
+    matcher: SessionMatcher,
+    device: String,
+}
+
+fn load_routes(config: &Config) -> Vec<Route> {
+    config
+        .sessions
+        .iter()
+        .filter_map(|session| {
+            let device = session.output_device.clone()?;
+            let matcher = SessionMatcher::new(session).ok()?;
+            Some(Route { matcher, device })
+        })
+        .collect()
+}
+
+/// Route running apps now and again whenever the config changes. New sessions are routed by the session monitor.
+pub fn start(app_handle: AppHandle) {
+    apply_configured_routes(config::get_config());
+
+    app_handle.listen(AppEvent::ConfigChange.as_str(), |event: Event| {
+        if let Ok(config) = serde_json::from_str::<Config>(event.payload()) {
+            apply_configured_routes(config);
+        }
+    });
+}
+
+fn apply_configured_routes(config: Config) {
+    audio_worker::run(move |backend| {
+        let sessions = backend.sessions();
+        route_sessions(backend, &sessions, &load_routes(&config));
+    });
+}
+
+/// Send sessions that have an `output_device` configured to it. Called from audio worker jobs.
+pub fn apply_routes(backend: &mut dyn AudioBackend, sessions: &[BackendSession]) {
+    let routes = load_routes(&config::get_config());
+    if routes.is_empty() {
+        return;
+    }
+    route_sessions(backend, sessions, &routes);
+}
+
+/// Send a session's app to an output, or back to the default one with None. Windows remembers the choice for the app.
+pub fn set_session_device(session_id: &str, device_id: Option<&str>) -> bool {
+    let id = session_id.to_string();
+    let device_id = device_id.map(str::to_string);
+    audio_worker::run(move |backend| apply_session_device(backend, &id, device_id.as_deref()))
+}
+
+fn apply_session_device(backend: &mut dyn AudioBackend, session_id: &str, device_id: Option<&str>) -> bool {
+    // Picking the current default is the same as following the default
+    let devices = backend.devices();
+    let device_id = device_id.filter(|device_id| {
+        !devices
+            .iter()
+            .any(|device| device.kind == DeviceKind::Output && device.is_default && device.id == *device_id)
+    });
+
+    if !backend.set_session_device(session_id, device_id) {
+        log::warn!("Set Session Device: No Session Or Device Found: {} -> {:?}", session_id, device_id);
+        return false;
+    }
+
+    log::info!("Routing session {} -> {}", session_id, device_id.unwrap_or("default"));
+    true
+}
+
+fn route_sessions(backend: &mut dyn AudioBackend, sessions: &[BackendSession], routes: &[Route]) {
+    let devices = backend.devices();
+
+    for session in sessions.iter().filter(|session| session.pid != 0) {
+        let Some(route) = routes.iter().find(|route| route.matcher.matches(session)) else {
+            continue;
+        };
+        let Some(device) = find_output(&devices, &route.device) else {
+            log::warn!("Can't route {}: no output device matches {}", session.name, route.device);
+            continue;
+        };
+        if session.device_id.as_deref() == Some(device.id.as_str()) {
+            continue;
+        }
+
+        log::info!("Routing {} ({}) to {}", session.name, route.matcher.name(), device.name);
+        backend.set_session_device(&session.id, Some(&device.id));
+    }
+}
+
+// Endpoint ids are exact, names may use wildcards
+fn find_output<'a>(devices: &'a [BackendDevice], wanted: &str) -> Option<&'a BackendDevice> {
+    let outputs = || devices.iter().filter(|device| device.kind == DeviceKind::Output);
+
+    outputs()
+        .find(|device| device.id == wanted)
+        .or_else(|| outputs().find(|device| session_matcher::glob_matches(wanted, &device.name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SessionConfig;
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn route(name: &str, device: &str) -> Route {
+        let mut config = SessionConfig::new(name);
+        config.output_device = Some(device.to_string());
+        Route {
+            matcher: SessionMatcher::new(&config).unwrap(),
+            device: device.to_string(),
+        }
+    }
+
+    fn backend() -> MockAudioBackend {
+        MockAudioBackend::new()
+            .with_device("Speakers (Realtek Audio)", 0.5, false, true)
+            .with_device("Headphones (USB Audio)", 0.5, false, false)
+            .with_input("Microphone (USB Audio)", 0.5, false, true)
+            .with_session("master", 0.5, false)
+            .with_session("discord", 0.5, false)
+            .with_session("chrome", 0.5, false)
+    }
+
+    #[test]
+    fn configured_sessions_are_routed() {
+        let mut backend = backend();
+        let sessions = backend.sessions();
+
+        route_sessions(&mut backend, &sessions, &[route("discord", "Headphones*")]);
+
+        assert_eq!(backend.named("discord").unwrap().device_id.as_deref(), Some("device-2"));
+        assert_eq!(backend.named("chrome").unwrap().device_id, None);
+        assert_eq!(backend.named("master").unwrap().device_id, None);
+    }
+
+    #[test]
+    fn devices_match_by_id_or_name_but_only_outputs() {
+        let backend = backend();
+        let devices = backend.devices();
+
+        assert_eq!(find_output(&devices, "device-2").unwrap().name, "Headphones (USB Audio)");
+        assert_eq!(find_output(&devices, "speakers*").unwrap().id, "device-1");
+        assert!(find_output(&devices, "Microphone*").is_none());
+        assert!(find_output(&devices, "HDMI").is_none());
+    }
+
+    #[test]
+    fn unknown_device_leaves_session_alone() {
+        let mut backend = backend();
+        let sessions = backend.sessions();
+
+        route_sessions(&mut backend, &sessions, &[route("discord", "HDMI")]);
+
+        assert_eq!(backend.named("discord").unwrap().device_id, None);
+    }
+
+    #[test]
+    fn picking_the_default_device_clears_the_route() {
+        let mut backend = backend();
+        let discord = backend.named("discord").unwrap().id.clone();
+
+        assert!(apply_session_device(&mut backend, &discord, Some("device-2")));
+        assert_eq!(backend.session(&discord).unwrap().device_id.as_deref(), Some("device-2"));
+
+        assert!(apply_session_device(&mut backend, &discord, Some("device-1")));
+        assert_eq!(backend.session(&discord).unwrap().device_id, None);
+
+        assert!(!apply_session_device(&mut backend, "master", Some("device-2")));
+        assert!(!apply_session_device(&mut backend, &discord, Some("device-3")));
+    }
+}
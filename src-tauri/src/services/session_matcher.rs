@@ -0,0 +1,294 @@
+use regex::Regex;
+
+use crate::config::SessionConfig;
+
+use super::audio_backend::BackendSession;
+
+/// A config entry compiled for matching against live sessions.
+///
+/// Entries without exe, title, pid or instance_id match on name. Otherwise every one of those
+/// that is set has to match, which is what tells apart two sessions sharing a name. Aliases
+/// always match on their own, so one entry can cover several apps or an app's old and new names.
+/// Names, aliases and exe accept `*` and `?` wildcards and ignore case.
+pub struct SessionMatcher {
+    name: String,
+    aliases: Vec<String>,
+    exe: Option<String>,
+    title: Option<Regex>,
+    pid: Option<u32>,
+    instance_id: Option<String>,
+}
+
+impl SessionMatcher {
+    /// Fails only when `title` is not a valid regex.
+    pub fn new(config: &SessionConfig) -> Result<Self, String> {
+        let title = match &config.title {
+            Some(title) => Some(Regex::new(title).map_err(|e| format!("Invalid title pattern: {}", e))?),
+            None => None,
+        };
+
+        Ok(SessionMatcher {
+            name: config.name.clone(),
+            aliases: config.aliases.clone().unwrap_or_default(),
+            exe: config.exe.as_deref().map(normalize_path),
+            title,
+            pid: config.pid,
+            instance_id: config.instance_id.clone(),
+        })
+    }
+
+    /// Compile every entry, skipping (and logging) the ones that can't be.
+    pub fn compile_all(configs: &[SessionConfig]) -> Vec<SessionMatcher> {
+        configs
+            .iter()
+            .filter_map(|config| match SessionMatcher::new(config) {
+                Ok(matcher) => Some(matcher),
+                Err(e) => {
+                    log::warn!("Ignoring session {}: {}", config.name, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the window title is what decides this match, so it's worth looking up for `session`. Sessions the
+    /// alias, instance id, pid or exe already settle are left out.
+    pub fn needs_title(&self, session: &BackendSession) -> bool {
+        if self.title.is_none() || self.aliases.iter().any(|alias| glob_matches(alias, &session.name)) {
+            return false;
+        }
+
+        let ruled_out = self.instance_id.as_ref().is_some_and(|instance_id| session.id != *instance_id)
+            || self.pid.is_some_and(|pid| session.pid != pid)
+            || self
+                .exe
+                .as_ref()
+                .is_some_and(|exe| !session.exe_path.as_deref().is_some_and(|exe_path| exe_matches(exe, exe_path)));
+        !ruled_out
+    }
+
+    pub fn matches(&self, session: &BackendSession) -> bool {
+        if self.aliases.iter().any(|alias| glob_matches(alias, &session.name)) {
+            return true;
+        }
+
+        let mut has_identity = false;
+
+        if let Some(instance_id) = &self.instance_id {
+            has_identity = true;
+            if session.id != *instance_id {
+                return false;
+            }
+        }
+
+        if let Some(pid) = self.pid {
+            has_identity = true;
+            if session.pid != pid {
+                return false;
+            }
+        }
+
+        if let Some(exe) = &self.exe {
+            has_identity = true;
+            if !session.exe_path.as_deref().is_some_and(|exe_path| exe_matches(exe, exe_path)) {
+                return false;
+            }
+        }
+
+        if let Some(title) = &self.title {
+            has_identity = true;
+            if !session.title.as_deref().is_some_and(|text| title.is_match(text)) {
+                return false;
+            }
+        }
+
+        has_identity || glob_matches(&self.name, &session.name)
+    }
+}
+
+// A file name pattern matches any install of the app, a path pattern only that location
+fn exe_matches(exe: &str, exe_path: &str) -> bool {
+    let exe_path = normalize_path(exe_path);
+
+    if exe.contains('\\') {
+        return glob_matches(exe, &exe_path);
+    }
+
+    let file_name = exe_path.rsplit('\\').next().unwrap_or_default();
+    glob_matches(exe, file_name) || file_name.strip_suffix(".exe").is_some_and(|stem| glob_matches(exe, stem))
+}
+
+fn normalize_path(path: &str) -> String {
+    path.trim().replace('/', "\\").to_lowercase()
+}
+
+// Case-insensitive match where `*` is any run of characters and `?` a single one
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and how much of the text it has swallowed so far
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, swallowed)) => {
+                    p = star + 1;
+                    t = swallowed + 1;
+                    backtrack = Some((star, swallowed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, pid: u32, exe_path: &str) -> BackendSession {
+        BackendSession {
+            id: format!("{}|{}", exe_path, pid),
+            name: name.to_string(),
+            pid,
+            exe_path: Some(exe_path.to_string()),
+            title: None,
+            device_id: None,
+            volume: 1.0,
+            mute: false,
+        }
+    }
+
+    fn matcher(config: SessionConfig) -> SessionMatcher {
+        SessionMatcher::new(&config).unwrap()
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_matches("chrome", "Chrome"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("steam*", "steamwebhelper"));
+        assert!(glob_matches("*.exe", "game.exe"));
+        assert!(glob_matches("c:\\games\\*\\game.exe", "c:\\games\\steam\\game.exe"));
+        assert!(glob_matches("fire?ox", "firefox"));
+        assert!(glob_matches("*a*b", "xaxxab"));
+        assert!(!glob_matches("chrome", "chromium"));
+        assert!(!glob_matches("fire?ox", "firebox2"));
+        assert!(!glob_matches("*a*b", "xaxxba"));
+    }
+
+    #[test]
+    fn name_only_entries_match_by_name() {
+        let config = matcher(SessionConfig::new("discord"));
+
+        assert!(config.matches(&session("Discord", 1, "C:\\Discord\\Discord.exe")));
+        assert!(!config.matches(&session("chrome", 2, "C:\\Chrome\\chrome.exe")));
+    }
+
+    #[test]
+    fn aliases_let_one_entry_cover_several_apps() {
+        let mut config = SessionConfig::new("Browsers");
+        config.aliases = Some(vec!["chrome".to_string(), "msedge".to_string(), "firefox*".to_string()]);
+        let config = matcher(config);
+
+        assert!(config.matches(&session("chrome", 1, "C:\\Chrome\\chrome.exe")));
+        assert!(config.matches(&session("msedge", 2, "C:\\Edge\\msedge.exe")));
+        assert!(config.matches(&session("firefox-nightly", 3, "C:\\Firefox\\firefox-nightly.exe")));
+        assert!(config.matches(&session("Browsers", 4, "C:\\Other\\Browsers.exe")));
+        assert!(!config.matches(&session("discord", 5, "C:\\Discord\\discord.exe")));
+    }
+
+    #[test]
+    fn exe_file_name_matches_any_install() {
+        let mut config = SessionConfig::new("Game");
+        config.exe = Some("game.exe".to_string());
+        let config = matcher(config);
+
+        assert!(config.matches(&session("game", 1, "C:\\Steam\\Game\\Game.exe")));
+        assert!(config.matches(&session("game", 2, "D:\\Epic\\Game\\game.exe")));
+        assert!(!config.matches(&session("game", 3, "D:\\Epic\\Game\\launcher.exe")));
+
+        let mut config = SessionConfig::new("Game");
+        config.exe = Some("game".to_string());
+        assert!(matcher(config).matches(&session("game", 1, "C:\\Steam\\Game\\Game.exe")));
+    }
+
+    #[test]
+    fn exe_path_tells_installs_apart() {
+        let mut config = SessionConfig::new("Steam games");
+        config.exe = Some("c:/steam/*".to_string());
+        let config = matcher(config);
+
+        assert!(config.matches(&session("game", 1, "C:\\Steam\\Game\\Game.exe")));
+        assert!(!config.matches(&session("game", 2, "D:\\Epic\\Game\\game.exe")));
+    }
+
+    #[test]
+    fn title_regex_matches_window_title() {
+        let mut config = SessionConfig::new("Music");
+        config.title = Some("(?i)spotify|youtube music".to_string());
+        let config = matcher(config);
+
+        let mut player = session("chrome", 1, "C:\\Chrome\\chrome.exe");
+        assert!(!config.matches(&player));
+
+        player.title = Some("Lofi beats - YouTube Music".to_string());
+        assert!(config.matches(&player));
+
+        player.title = Some("Inbox - Mail".to_string());
+        assert!(!config.matches(&player));
+    }
+
+    #[test]
+    fn title_is_only_needed_where_it_decides_the_match() {
+        let mut config = SessionConfig::new("Music");
+        config.exe = Some("chrome.exe".to_string());
+        config.title = Some("YouTube Music".to_string());
+        let config = matcher(config);
+
+        assert!(config.needs_title(&session("chrome", 1, "C:\\Chrome\\chrome.exe")));
+        assert!(!config.needs_title(&session("discord", 2, "C:\\Discord\\discord.exe")));
+        assert!(!matcher(SessionConfig::new("chrome")).needs_title(&session("chrome", 1, "C:\\Chrome\\chrome.exe")));
+    }
+
+    #[test]
+    fn invalid_title_pattern_is_rejected() {
+        let mut config = SessionConfig::new("Broken");
+        config.title = Some("(unclosed".to_string());
+
+        assert!(SessionMatcher::new(&config).is_err());
+        assert!(SessionMatcher::compile_all(&[config]).is_empty());
+    }
+
+    #[test]
+    fn identity_fields_override_the_name() {
+        let target = session("player", 7, "C:\\Player\\player.exe");
+
+        let mut config = SessionConfig::new("Music");
+        config.pid = Some(7);
+        assert!(matcher(config.clone()).matches(&target));
+
+        config.instance_id = Some("something else".to_string());
+        assert!(!matcher(config.clone()).matches(&target));
+
+        config.instance_id = Some(target.id.clone());
+        assert!(matcher(config).matches(&target));
+    }
+}
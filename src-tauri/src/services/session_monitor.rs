@@ -0,0 +1,237 @@
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::api::events;
+use crate::models::audio_session::AudioSession;
+
+use super::audio_backend::{AudioBackend, BackendSession};
+use super::volume_curve::VolumeCurve;
+use super::{audio_worker, routing_service, state_service, volume_service};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+static MONITOR: Lazy<Mutex<SessionMonitor>> = Lazy::new(|| Mutex::new(SessionMonitor::new()));
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    Added(BackendSession),
+    Removed(BackendSession),
+    // Volume or mute changed by something other than Maestro
+    Changed(BackendSession),
+}
+
+/// Turns successive session snapshots from a backend into added/removed/changed events.
+#[derive(Default)]
+pub struct SessionMonitor {
+    known: Option<Vec<BackendSession>>,
+}
+
+impl SessionMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare the backend against the last snapshot. The first poll only records a baseline.
+    pub fn poll(&mut self, backend: &dyn AudioBackend) -> Vec<SessionEvent> {
+        let sessions = backend.sessions();
+        let Some(known) = self.known.replace(sessions.clone()) else {
+            return Vec::new();
+        };
+
+        let mut changes = Vec::new();
+
+        for session in &sessions {
+            match known.iter().find(|existing| existing.id == session.id) {
+                None => changes.push(SessionEvent::Added(session.clone())),
+                Some(existing) if !same_levels(existing, session) => changes.push(SessionEvent::Changed(session.clone())),
+                Some(_) => {}
+            }
+        }
+
+        for existing in known {
+            if !sessions.iter().any(|session| session.id == existing.id) {
+                changes.push(SessionEvent::Removed(existing));
+            }
+        }
+
+        changes
+    }
+
+    /// Record levels Maestro set itself, so the next poll doesn't report them as external changes.
+    pub fn acknowledge(&mut self, sessions: &[BackendSession]) {
+        let Some(known) = &mut self.known else {
+            return;
+        };
+
+        for session in sessions {
+            if let Some(existing) = known.iter_mut().find(|existing| existing.id == session.id) {
+                *existing = session.clone();
+            }
+        }
+    }
+}
+
+// Sub-percent drift isn't something the UI can show
+fn same_levels(a: &BackendSession, b: &BackendSession) -> bool {
+    a.mute == b.mute && (a.volume * 100.0).round() == (b.volume * 100.0).round()
+}
+
+/// Called from audio worker jobs that change levels, with the sessions they touched.
+pub fn acknowledge(sessions: &[BackendSession]) {
+    MONITOR.lock().unwrap().acknowledge(sessions);
+}
+
+/// Acknowledge levels the user picked, which are also saved to come back next time.
+pub fn acknowledge_and_remember(sessions: &[BackendSession]) {
+    acknowledge(sessions);
+    state_service::remember(sessions);
+}
+
+/// Poll for sessions appearing, disappearing or being changed outside Maestro, and emit events for them.
+pub fn start(app_handle: AppHandle) {
+    thread::Builder::new()
+        .name("session-monitor".to_string())
+        .spawn(move || loop {
+            // Diffing on the worker keeps polls ordered with our own changes and their acknowledgements
+            let (changes, channels) = audio_worker::run(|backend| {
+                let changes = MONITOR.lock().unwrap().poll(backend);
+                let channels = changed_channels(backend, &changes);
+                state_service::remember(&changed_sessions(&changes));
+
+                let added = added_sessions(&changes);
+                routing_service::apply_routes(backend, &added);
+                acknowledge(&state_service::restore(backend, &added));
+                (changes, channels)
+            });
+
+            emit_changes(&changes, &channels, &app_handle);
+            thread::sleep(POLL_INTERVAL);
+        })
+        .expect("Failed to start session monitor");
+}
+
+fn added_sessions(changes: &[SessionEvent]) -> Vec<BackendSession> {
+    changes
+        .iter()
+        .filter_map(|change| match change {
+            SessionEvent::Added(session) => Some(session.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn changed_sessions(changes: &[SessionEvent]) -> Vec<BackendSession> {
+    changes
+        .iter()
+        .filter_map(|change| match change {
+            SessionEvent::Changed(session) => Some(session.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn changed_channels(backend: &dyn AudioBackend, changes: &[SessionEvent]) -> Vec<AudioSession> {
+    let changed = changed_sessions(changes);
+    if changed.is_empty() {
+        return Vec::new();
+    }
+    volume_service::affected_channels(backend, &changed)
+}
+
+fn emit_changes(changes: &[SessionEvent], channels: &[AudioSession], app_handle: &AppHandle) {
+    for change in changes {
+        match change {
+            SessionEvent::Added(session) => {
+                log::info!("Session added: {} ({})", session.name, session.pid);
+                let session = AudioSession::from_session(session, &VolumeCurve::load());
+                events::emit_session_added_event(&session, app_handle.clone());
+            }
+            SessionEvent::Removed(session) => {
+                log::info!("Session removed: {} ({})", session.name, session.pid);
+                let session = AudioSession::from_session(session, &VolumeCurve::load());
+                events::emit_session_removed_event(&session, app_handle.clone());
+            }
+            SessionEvent::Changed(_) => {}
+        }
+    }
+
+    for channel in channels {
+        log::debug!("External volume change: {} {} {}", channel.name, channel.volume, channel.mute);
+        events::emit_external_volume_change_event(channel, app_handle.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn names(changes: &[SessionEvent]) -> Vec<String> {
+        changes
+            .iter()
+            .map(|change| match change {
+                SessionEvent::Added(session) => format!("+{}", session.name),
+                SessionEvent::Removed(session) => format!("-{}", session.name),
+                SessionEvent::Changed(session) => format!("~{}", session.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_poll_is_a_baseline() {
+        let backend = MockAudioBackend::new().with_session("master", 0.5, false);
+        let mut monitor = SessionMonitor::new();
+
+        assert!(monitor.poll(&backend).is_empty());
+        assert!(monitor.poll(&backend).is_empty());
+    }
+
+    #[test]
+    fn reports_added_and_removed_sessions() {
+        let mut backend = MockAudioBackend::new().with_session("master", 0.5, false);
+        let mut monitor = SessionMonitor::new();
+        monitor.poll(&backend);
+
+        let chrome = backend.add_session("chrome", 0.5, false);
+        assert_eq!(names(&monitor.poll(&backend)), vec!["+chrome"]);
+
+        backend.remove_session(&chrome);
+        backend.add_session("discord", 0.5, false);
+        assert_eq!(names(&monitor.poll(&backend)), vec!["+discord", "-chrome"]);
+    }
+
+    #[test]
+    fn reports_external_volume_and_mute_changes() {
+        let mut backend = MockAudioBackend::new().with_session("master", 0.5, false);
+        let chrome = backend.add_session("chrome", 0.5, false);
+        let mut monitor = SessionMonitor::new();
+        monitor.poll(&backend);
+
+        backend.set_volume(&chrome, 0.7);
+        let changes = monitor.poll(&backend);
+        assert_eq!(names(&changes), vec!["~chrome"]);
+        assert_eq!(changes[0], SessionEvent::Changed(backend.session(&chrome).unwrap().clone()));
+
+        backend.set_mute("master", true);
+        assert_eq!(names(&monitor.poll(&backend)), vec!["~master"]);
+
+        // Drift below a percent is ignored
+        backend.set_volume(&chrome, 0.701);
+        assert!(monitor.poll(&backend).is_empty());
+    }
+
+    #[test]
+    fn acknowledged_changes_are_not_reported() {
+        let mut backend = MockAudioBackend::new().with_session("master", 0.5, false);
+        let mut monitor = SessionMonitor::new();
+        monitor.poll(&backend);
+
+        backend.set_volume("master", 0.2);
+        monitor.acknowledge(&backend.sessions());
+
+        assert!(monitor.poll(&backend).is_empty());
+    }
+}
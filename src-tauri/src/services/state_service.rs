@@ -0,0 +1,279 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Event, Listener};
+
+use crate::api::events::AppEvent;
+use crate::config::{self, storage, Config};
+
+use super::audio_backend::{AudioBackend, BackendSession};
+use super::session_matcher::SessionMatcher;
+
+// Levels are written once they have settled for this long, so a held volume key doesn't write on every step
+const FLUSH_DELAY: Duration = Duration::from_secs(1);
+const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+static STATE: Lazy<Mutex<SessionState>> = Lazy::new(|| Mutex::new(load_state()));
+static ENTRIES: Lazy<Mutex<Vec<Entry>>> = Lazy::new(|| Mutex::new(load_entries(&config::get_config())));
+// When the levels last changed, while that change is still unwritten
+static LAST_CHANGE: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedLevel {
+    pub volume: f32,
+    pub mute: bool,
+}
+
+/// Last known levels of configured sessions, keyed by the session entry and the app it matched, so an entry
+/// matching several apps keeps a level for each.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    #[serde(default)]
+    sessions: BTreeMap<String, SavedLevel>,
+}
+
+/// A session entry, and whether its levels should be remembered.
+struct Entry {
+    matcher: SessionMatcher,
+    remember: bool,
+}
+
+fn load_entries(config: &Config) -> Vec<Entry> {
+    config
+        .sessions
+        .iter()
+        .filter_map(|session| {
+            let matcher = SessionMatcher::new(session).ok()?;
+            Some(Entry {
+                matcher,
+                remember: session.remember.unwrap_or(true),
+            })
+        })
+        .collect()
+}
+
+fn state_key(entry: &Entry, session: &BackendSession) -> String {
+    format!("{}/{}", entry.matcher.name(), session.name.to_lowercase())
+}
+
+// Like volume groups the first matching entry owns the session, even if it opted out
+fn remembering_entry<'a>(entries: &'a [Entry], session: &BackendSession) -> Option<&'a Entry> {
+    if session.pid == 0 {
+        return None;
+    }
+    entries
+        .iter()
+        .find(|entry| entry.matcher.matches(session))
+        .filter(|entry| entry.remember)
+}
+
+impl SessionState {
+    /// Record the levels of sessions that belong to a remembering entry. Returns whether anything changed.
+    fn remember(&mut self, entries: &[Entry], sessions: &[BackendSession]) -> bool {
+        let mut changed = false;
+
+        for session in sessions {
+            let Some(entry) = remembering_entry(entries, session) else {
+                continue;
+            };
+            let level = SavedLevel {
+                volume: session.volume,
+                mute: session.mute,
+            };
+            if self.sessions.insert(state_key(entry, session), level) != Some(level) {
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    /// Put the saved levels back on newly started sessions, returning the ones that changed.
+    fn restore(&self, backend: &mut dyn AudioBackend, entries: &[Entry], sessions: &[BackendSession]) -> Vec<BackendSession> {
+        let mut restored = Vec::new();
+
+        for session in sessions {
+            let Some(entry) = remembering_entry(entries, session) else {
+                continue;
+            };
+            let Some(level) = self.sessions.get(&state_key(entry, session)) else {
+                continue;
+            };
+            if session.volume == level.volume && session.mute == level.mute {
+                continue;
+            }
+
+            log::info!(
+                "Restoring {} ({}) -> {} {}",
+                session.name,
+                entry.matcher.name(),
+                level.volume,
+                level.mute
+            );
+            backend.set_volume(&session.id, level.volume);
+            backend.set_mute(&session.id, level.mute);
+            restored.extend(backend.sessions().into_iter().find(|s| s.id == session.id));
+        }
+
+        restored
+    }
+}
+
+/// Save the levels of configured sessions. Called from audio worker jobs with the sessions whose levels changed.
+/// Ducking's own changes never get here, so a level the user sets while a session is ducked is the one kept.
+pub fn remember(sessions: &[BackendSession]) {
+    let entries = ENTRIES.lock().unwrap();
+    if STATE.lock().unwrap().remember(&entries, sessions) {
+        *LAST_CHANGE.lock().unwrap() = Some(Instant::now());
+    }
+}
+
+/// Re-apply saved levels to sessions that just appeared. Called from audio worker jobs.
+pub fn restore(backend: &mut dyn AudioBackend, sessions: &[BackendSession]) -> Vec<BackendSession> {
+    if sessions.is_empty() {
+        return Vec::new();
+    }
+    let entries = ENTRIES.lock().unwrap();
+    STATE.lock().unwrap().restore(backend, &entries, sessions)
+}
+
+/// Write remembered levels to disk once they settle, and follow config changes to the session entries.
+pub fn start(app_handle: AppHandle) {
+    app_handle.listen(AppEvent::ConfigChange.as_str(), |event: Event| {
+        if let Ok(config) = serde_json::from_str::<Config>(event.payload()) {
+            *ENTRIES.lock().unwrap() = load_entries(&config);
+        }
+    });
+
+    thread::Builder::new()
+        .name("state-flush".to_string())
+        .spawn(|| loop {
+            thread::sleep(FLUSH_POLL_INTERVAL);
+            let settled = LAST_CHANGE.lock().unwrap().is_some_and(|since| since.elapsed() >= FLUSH_DELAY);
+            if settled {
+                flush();
+            }
+        })
+        .expect("Failed to start state flushing");
+}
+
+/// Write remembered levels now if any are unsaved. Called on quit, so nothing waiting for the delay is lost.
+pub fn flush() {
+    if LAST_CHANGE.lock().unwrap().take().is_none() {
+        return;
+    }
+
+    // The state is small and rewritten often, so no backups
+    let result = config::state_file_path().map_err(|e| e.to_string()).and_then(|file_path| {
+        let content = serde_json::to_string_pretty(&*STATE.lock().unwrap()).map_err(|e| e.to_string())?;
+        storage::write_atomic(&file_path, &content, 0).map_err(|e| e.to_string())
+    });
+
+    if let Err(e) = result {
+        log::error!("Failed to save session state: {}", e);
+    }
+}
+
+// A missing or unreadable file only means nothing is remembered yet
+fn load_state() -> SessionState {
+    let Ok(file_path) = config::state_file_path() else {
+        return SessionState::default();
+    };
+    let Ok(content) = fs::read_to_string(&file_path) else {
+        return SessionState::default();
+    };
+
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!("Ignoring unreadable state file {}: {}", file_path.display(), e);
+        SessionState::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SessionConfig;
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn entry(name: &str, remember: Option<bool>) -> Entry {
+        let mut config = SessionConfig::new(name);
+        config.remember = remember;
+        Entry {
+            matcher: SessionMatcher::new(&config).unwrap(),
+            remember: remember.unwrap_or(true),
+        }
+    }
+
+    fn backend() -> MockAudioBackend {
+        MockAudioBackend::new()
+            .with_session("master", 0.5, false)
+            .with_session("discord", 0.3, true)
+            .with_session("chrome", 0.8, false)
+    }
+
+    #[test]
+    fn remembers_configured_sessions_only() {
+        let backend = backend();
+        let mut state = SessionState::default();
+        let entries = [entry("discord", None), entry("master", None)];
+
+        assert!(state.remember(&entries, &backend.sessions()));
+        assert!(!state.remember(&entries, &backend.sessions()));
+
+        assert_eq!(state.sessions.len(), 1);
+        assert_eq!(state.sessions["discord/discord"], SavedLevel { volume: 0.3, mute: true });
+    }
+
+    #[test]
+    fn opted_out_entries_are_not_remembered() {
+        let backend = backend().with_session("Spotify", 0.4, false);
+        let mut state = SessionState::default();
+
+        // The first match decides, so a later catch-all can't pick discord up
+        let entries = [entry("discord", Some(false)), entry("*", None)];
+        state.remember(&entries, &backend.sessions());
+
+        // Every app the catch-all matches keeps its own level
+        assert_eq!(state.sessions.keys().collect::<Vec<_>>(), vec!["*/chrome", "*/spotify"]);
+        assert_eq!(state.sessions["*/chrome"].volume, 0.8);
+        assert_eq!(state.sessions["*/spotify"].volume, 0.4);
+    }
+
+    #[test]
+    fn restores_saved_levels_on_new_sessions() {
+        let mut backend = backend();
+        let mut state = SessionState::default();
+        let entries = [entry("discord", None), entry("chrome", None)];
+        state.remember(&entries, &backend.sessions());
+
+        let discord = backend.named("discord").unwrap().id.clone();
+        backend.remove_session(&discord);
+        let discord = backend.add_session("discord", 1.0, false);
+        let added = vec![backend.session(&discord).unwrap().clone(), backend.named("chrome").unwrap().clone()];
+
+        let restored = state.restore(&mut backend, &entries, &added);
+
+        // Chrome already has its saved levels
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].id, discord);
+        assert_eq!(backend.session(&discord).unwrap().volume, 0.3);
+        assert!(backend.session(&discord).unwrap().mute);
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let mut state = SessionState::default();
+        state
+            .sessions
+            .insert("discord".to_string(), SavedLevel { volume: 0.25, mute: false });
+
+        let json = serde_json::to_string(&state).unwrap();
+
+        assert_eq!(serde_json::from_str::<SessionState>(&json).unwrap(), state);
+        assert_eq!(serde_json::from_str::<SessionState>("{}").unwrap(), SessionState::default());
+    }
+}
@@ -0,0 +1,212 @@
+use crate::config::{self, CurveKind, VolumeConfig};
+
+use super::audio_backend::BackendSession;
+
+// Loudness range the logarithmic curve spreads over the slider, quiet end to full volume
+const LOGARITHMIC_RANGE_DB: f32 = 40.0;
+const LOGARITHMIC_SEGMENTS: usize = 20;
+
+/// Quietest level shown in dB. Silence is reported as this too.
+pub const MIN_DECIBELS: f32 = -96.0;
+
+static LINEAR: VolumeCurve = VolumeCurve::Linear;
+
+/// Maps a slider position (0-100) to the volume scalar the backend takes, and back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VolumeCurve {
+    Linear,
+    // Slider and scalar pairs, both 0.0-1.0 and rising, from (0, 0) to (1, 1)
+    Points(Vec<(f32, f32)>),
+}
+
+impl VolumeCurve {
+    pub fn new(config: &VolumeConfig) -> Self {
+        match config.curve {
+            CurveKind::Linear => VolumeCurve::Linear,
+            CurveKind::Logarithmic => logarithmic(),
+            CurveKind::Custom => VolumeCurve::custom(&config.points).unwrap_or_else(|e| {
+                log::warn!("Using a linear volume curve: {}", e);
+                VolumeCurve::Linear
+            }),
+        }
+    }
+
+    pub fn load() -> Self {
+        VolumeCurve::new(&config::get_config().volume)
+    }
+
+    /// A curve through `[slider, volume]` points. The ends are pinned to 0 and 100 when the points leave them out.
+    pub fn custom(points: &[[i32; 2]]) -> Result<Self, String> {
+        if points.is_empty() {
+            return Err("a custom curve needs points".to_string());
+        }
+        if let Some([slider, volume]) = points.iter().find(|point| point.iter().any(|value| !(0..=100).contains(value))) {
+            return Err(format!("point [{}, {}] is out of range, expected 0-100", slider, volume));
+        }
+
+        let mut points: Vec<[i32; 2]> = points.to_vec();
+        if points[0] != [0, 0] {
+            points.insert(0, [0, 0]);
+        }
+        if points[points.len() - 1] != [100, 100] {
+            points.push([100, 100]);
+        }
+
+        // Both have to keep rising so every slider position has its own volume, and every volume its position
+        if let Some(pair) = points.windows(2).find(|pair| pair[1][0] <= pair[0][0] || pair[1][1] <= pair[0][1]) {
+            return Err(format!(
+                "points have to rise from [0, 0] to [100, 100], but [{}, {}] follows [{}, {}]",
+                pair[1][0], pair[1][1], pair[0][0], pair[0][1]
+            ));
+        }
+
+        Ok(VolumeCurve::Points(
+            points
+                .iter()
+                .map(|[slider, volume]| (*slider as f32 / 100.0, *volume as f32 / 100.0))
+                .collect(),
+        ))
+    }
+
+    /// The curve for `session`. Master is the endpoint volume, which Windows already tapers, so it stays linear.
+    pub fn for_session(&self, session: &BackendSession) -> &VolumeCurve {
+        if session.pid == 0 {
+            return &LINEAR;
+        }
+        self
+    }
+
+    pub fn to_scalar(&self, position: i32) -> f32 {
+        let position = position.clamp(0, 100) as f32 / 100.0;
+        match self {
+            VolumeCurve::Linear => position,
+            VolumeCurve::Points(points) => interpolate(points.iter().copied(), position),
+        }
+    }
+
+    pub fn to_position(&self, scalar: f32) -> i32 {
+        let scalar = scalar.clamp(0.0, 1.0);
+        let position = match self {
+            VolumeCurve::Linear => scalar,
+            VolumeCurve::Points(points) => interpolate(points.iter().map(|(slider, volume)| (*volume, *slider)), scalar),
+        };
+        (position * 100.0).round() as i32
+    }
+}
+
+// Zero is silence rather than the bottom of the range, the slope up to the first step covers the gap
+fn logarithmic() -> VolumeCurve {
+    let points = (0..=LOGARITHMIC_SEGMENTS)
+        .map(|segment| {
+            let slider = segment as f32 / LOGARITHMIC_SEGMENTS as f32;
+            let volume = match segment {
+                0 => 0.0,
+                _ => 10f32.powf((slider - 1.0) * LOGARITHMIC_RANGE_DB / 20.0),
+            };
+            (slider, volume)
+        })
+        .collect();
+    VolumeCurve::Points(points)
+}
+
+// Straight lines between rising points
+fn interpolate(points: impl Iterator<Item = (f32, f32)>, x: f32) -> f32 {
+    let mut previous: Option<(f32, f32)> = None;
+
+    for (x1, y1) in points {
+        if let Some((x0, y0)) = previous {
+            if x <= x1 {
+                return y0 + (y1 - y0) * (x - x0) / (x1 - x0);
+            }
+        }
+        previous = Some((x1, y1));
+    }
+
+    previous.map_or(x, |(_, y)| y)
+}
+
+/// A volume scalar in dB, rounded to a tenth.
+pub fn decibels(scalar: f32) -> f32 {
+    if scalar <= 0.0 {
+        return MIN_DECIBELS;
+    }
+    ((20.0 * scalar.log10()).max(MIN_DECIBELS) * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trips(curve: &VolumeCurve) -> bool {
+        (0..=100).all(|position| curve.to_position(curve.to_scalar(position)) == position)
+    }
+
+    #[test]
+    fn linear_is_the_plain_percentage() {
+        let curve = VolumeCurve::new(&VolumeConfig::default());
+
+        assert_eq!(curve.to_scalar(35), 0.35);
+        assert_eq!(curve.to_position(0.35), 35);
+        assert_eq!(curve.to_scalar(150), 1.0);
+        assert!(round_trips(&curve));
+    }
+
+    #[test]
+    fn logarithmic_spreads_the_quiet_end() {
+        let curve = logarithmic();
+
+        assert_eq!(curve.to_scalar(0), 0.0);
+        assert_eq!(curve.to_scalar(100), 1.0);
+        assert_eq!(decibels(curve.to_scalar(50)), -20.0);
+        assert_eq!(decibels(curve.to_scalar(75)), -10.0);
+        // A tenth of the level sits at the middle of the slider instead of near its bottom
+        assert_eq!(curve.to_position(0.1), 50);
+        assert!(round_trips(&curve));
+    }
+
+    #[test]
+    fn custom_points_are_pinned_at_the_ends() {
+        let curve = VolumeCurve::custom(&[[50, 20]]).unwrap();
+
+        assert_eq!(curve.to_scalar(25), 0.1);
+        assert_eq!(curve.to_scalar(75), 0.6);
+        assert_eq!(curve.to_position(0.2), 50);
+        assert!(round_trips(&curve));
+    }
+
+    #[test]
+    fn bad_custom_points_are_rejected() {
+        assert!(VolumeCurve::custom(&[]).is_err());
+        assert!(VolumeCurve::custom(&[[50, 120]]).is_err());
+        assert!(VolumeCurve::custom(&[[60, 30], [40, 50]]).is_err());
+        assert!(VolumeCurve::custom(&[[40, 30], [60, 30]]).is_err());
+        assert!(VolumeCurve::custom(&[[0, 0], [50, 40], [100, 100]]).is_ok());
+    }
+
+    #[test]
+    fn master_stays_linear() {
+        let curve = logarithmic();
+        let mut session = BackendSession {
+            id: "master".to_string(),
+            name: "master".to_string(),
+            pid: 0,
+            exe_path: None,
+            title: None,
+            device_id: None,
+            volume: 0.5,
+            mute: false,
+        };
+
+        assert_eq!(curve.for_session(&session).to_position(session.volume), 50);
+        session.pid = 42;
+        assert_eq!(curve.for_session(&session).to_position(session.volume), 85);
+    }
+
+    #[test]
+    fn decibels_bottom_out_at_silence() {
+        assert_eq!(decibels(1.0), 0.0);
+        assert_eq!(decibels(0.5), -6.0);
+        assert_eq!(decibels(0.0), MIN_DECIBELS);
+        assert_eq!(decibels(1e-9), MIN_DECIBELS);
+    }
+}
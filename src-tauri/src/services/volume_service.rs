@@ -1,145 +1,764 @@
-use std::{collections::HashMap, i32::MIN};
-use windows_volume_control::{AudioController, CoinitMode};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Event, Listener};
 
-use crate::{api::commands::get_session, config, models::audio_session::AudioSession};
+use crate::{
+    api::events::AppEvent,
+    config::{self, Config, GroupConfig, GroupMode},
+    models::audio_session::AudioSession,
+};
 
-fn get_audio_controller() -> AudioController {
-    unsafe {
-        let mut controller = AudioController::init(Some(CoinitMode::ApartmentThreaded));
-        controller.load_current_sessions();
-        return controller;
-    }
+use super::audio_backend::{AudioBackend, BackendSession};
+use super::session_matcher::SessionMatcher;
+use super::volume_curve::VolumeCurve;
+use super::{audio_worker, session_monitor};
+
+const MASTER_SESSION_ID: &str = "master";
+const OTHER_SESSION: &str = "other";
+
+static TARGETS: Lazy<Mutex<Arc<Targets>>> = Lazy::new(|| Mutex::new(Arc::new(Targets::new(&config::get_config()))));
+
+/// Session entries, groups and the volume curve from config, compiled once per config.
+pub struct Targets {
+    sessions: Vec<SessionMatcher>,
+    groups: Vec<GroupConfig>,
+    curve: VolumeCurve,
+    // Last mix of each relative group, by lowercased group name and then session id. Kept so a group pulled down
+    // to 0 comes back up with its members in the same proportions.
+    ratios: Mutex<HashMap<String, HashMap<String, f32>>>,
 }
 
-pub fn get_all_sessions() -> Vec<AudioSession> {
-    unsafe {
-        let controller = get_audio_controller();
-        let sessions = controller.get_all_sessions();
-        let mut session_map: HashMap<String, AudioSession> = HashMap::new();
-
-        for session in sessions {
-            let audio_session = AudioSession::from_session(session);
-            session_map.entry(audio_session.name.clone()).or_insert(audio_session);
+impl Targets {
+    pub fn new(config: &Config) -> Self {
+        let defined: Vec<_> = config
+            .sessions
+            .iter()
+            .filter(|session| !session.name.eq_ignore_ascii_case(OTHER_SESSION))
+            .cloned()
+            .collect();
+
+        Targets {
+            sessions: SessionMatcher::compile_all(&defined),
+            groups: config.groups.clone(),
+            curve: VolumeCurve::new(&config.volume),
+            ratios: Mutex::new(HashMap::new()),
         }
+    }
 
-        session_map.into_values().collect()
+    /// The targets for the running config.
+    pub fn load() -> Arc<Self> {
+        TARGETS.lock().unwrap().clone()
+    }
+
+    /// Whether a title rule could match `session`, which is all a window title is needed for.
+    pub fn needs_title(&self, session: &BackendSession) -> bool {
+        self.sessions.iter().any(|matcher| matcher.needs_title(session))
+    }
+
+    fn group(&self, name: &str) -> Option<&GroupConfig> {
+        self.groups.iter().find(|group| group.name.eq_ignore_ascii_case(name))
+    }
+
+    // Each member's volume as a share of the loudest one. With every member silent the last mix that had any
+    // volume is used instead.
+    fn ratios(&self, group: &str, members: &[BackendSession]) -> HashMap<String, f32> {
+        let key = group.to_lowercase();
+        let loudest = members.iter().map(|member| member.volume).fold(0.0, f32::max);
+        let mut ratios = self.ratios.lock().unwrap();
+
+        if loudest > 0.0 {
+            let mix = members.iter().map(|member| (member.id.clone(), member.volume / loudest)).collect();
+            ratios.insert(key.clone(), mix);
+        }
+        ratios.get(&key).cloned().unwrap_or_default()
     }
 }
 
-pub fn get_session_volume(session_name: &str) -> i32 {
-    unsafe {
-        let controller = get_audio_controller();
-        let session = controller.get_session_with_name(session_name.to_string());
+/// One slider's worth of audio: a single session, or every live member of a group.
+enum Channel {
+    Session(BackendSession),
+    Group(String, Vec<BackendSession>),
+}
 
-        if session.is_none() {
-            log::warn!("Get Volume: No Session Found: {}", session_name);
-            return MIN;
+impl Channel {
+    // A group is as loud as its loudest member and muted only when all of them are
+    fn volume(&self) -> f32 {
+        match self {
+            Channel::Session(session) => session.volume,
+            Channel::Group(_, members) => members.iter().map(|member| member.volume).fold(0.0, f32::max),
         }
+    }
 
-        return (session.unwrap().get_volume() * 100.0).round() as i32;
+    fn mute(&self) -> bool {
+        match self {
+            Channel::Session(session) => session.mute,
+            Channel::Group(_, members) => members.iter().all(|member| member.mute),
+        }
     }
-}
 
-pub fn get_sessions(session_name: &str) -> Vec<AudioSession> {
-    let controller = get_audio_controller();
-    let mut sessions;
+    fn to_audio_session(&self, curve: &VolumeCurve) -> AudioSession {
+        match self {
+            Channel::Session(session) => AudioSession::from_session(session, curve),
+            Channel::Group(name, _) => AudioSession::from_group(name, self.volume(), self.mute(), curve),
+        }
+    }
 
-    unsafe {
-        if session_name.to_lowercase() == "other" {
-            sessions = controller.get_all_sessions();
-            sessions.retain(|session| !config::get_defined_session_names().contains(&session.get_name().to_lowercase()));
-        } else {
-            sessions = controller.get_all_sessions_with_name(session_name.to_string());
+    // Where the channel's slider sits
+    fn position(&self, curve: &VolumeCurve) -> i32 {
+        match self {
+            Channel::Session(session) => curve.for_session(session).to_position(session.volume),
+            Channel::Group(..) => curve.to_position(self.volume()),
         }
     }
+}
+
+/// Recompile the targets whenever the config changes, and keep the backend looking up the window titles they need.
+pub fn start(app_handle: AppHandle) {
+    set_title_filter(Targets::load());
 
-    return sessions.into_iter().map(|session| AudioSession::from_session(session)).collect();
+    app_handle.listen(AppEvent::ConfigChange.as_str(), |event: Event| {
+        if let Ok(config) = serde_json::from_str::<Config>(event.payload()) {
+            let targets = Arc::new(Targets::new(&config));
+            *TARGETS.lock().unwrap() = targets.clone();
+            set_title_filter(targets);
+        }
+    });
 }
 
-pub fn set_session_volume(session_name: &str, volume: i32) -> Option<AudioSession> {
-    let mut volume = volume;
-    if volume < 0 {
-        volume = 0;
+fn set_title_filter(targets: Arc<Targets>) {
+    audio_worker::run(move |backend| backend.set_title_filter(Box::new(move |session| targets.needs_title(session))));
+}
+
+/// Everything the mixer shows: each group as one channel, then every session not in a group.
+pub fn get_all_sessions() -> Vec<AudioSession> {
+    let targets = Targets::load();
+    let curve = targets.curve.clone();
+    let channels = audio_worker::run(move |backend| mixer_channels(backend, &targets));
+    return channels.iter().map(|channel| channel.to_audio_session(&curve)).collect();
+}
+
+/// The channel addressed by `session_name`, as the mixer and overlay show it.
+pub fn get_session(session_name: &str) -> Option<AudioSession> {
+    let name = session_name.to_string();
+    let targets = Targets::load();
+    let curve = targets.curve.clone();
+    let channel = audio_worker::run(move |backend| {
+        refresh_on_miss(backend, &name, &targets);
+        resolve_channel(backend, &name, &targets)
+    });
+    return channel.as_ref().map(|channel| channel.to_audio_session(&curve));
+}
+
+pub fn get_session_volume(session_name: &str) -> i32 {
+    match get_session(session_name) {
+        Some(session) => session.volume,
+        None => {
+            log::warn!("Get Volume: No Session Found: {}", session_name);
+            i32::MIN
+        }
     }
-    if volume > 100 {
-        volume = 100;
+}
+
+pub fn set_session_volume(session_name: &str, volume: i32) -> Option<AudioSession> {
+    let name = session_name.to_string();
+    let targets = Targets::load();
+    let curve = targets.curve.clone();
+    let channel = audio_worker::run(move |backend| {
+        session_monitor::acknowledge_and_remember(&apply_session_volume(backend, &name, volume, &targets));
+        resolve_channel(backend, &name, &targets)
+    });
+    return channel.as_ref().map(|channel| channel.to_audio_session(&curve));
+}
+
+/// Move a session's volume by `delta` in a single worker request. Raising the volume also unmutes.
+pub fn step_session_volume(session_name: &str, delta: i32) -> Option<AudioSession> {
+    let name = session_name.to_string();
+    let targets = Targets::load();
+    let curve = targets.curve.clone();
+    let channel = audio_worker::run(move |backend| {
+        session_monitor::acknowledge_and_remember(&apply_volume_step(backend, &name, delta, &targets));
+        resolve_channel(backend, &name, &targets)
+    });
+    return channel.as_ref().map(|channel| channel.to_audio_session(&curve));
+}
+
+pub fn set_session_mute(session_name: &str, mute: bool) -> bool {
+    let name = session_name.to_string();
+    let targets = Targets::load();
+    return audio_worker::run(move |backend| {
+        let mute = apply_session_mute(backend, &name, mute, &targets);
+        session_monitor::acknowledge_and_remember(&find_sessions(backend, &name, &targets));
+        mute
+    });
+}
+
+pub fn toggle_session_mute(session_name: &str) -> Option<AudioSession> {
+    log::info!("TOGGLE MUTE: {}", session_name);
+    let name = session_name.to_string();
+    let targets = Targets::load();
+    let curve = targets.curve.clone();
+    let channel = audio_worker::run(move |backend| {
+        session_monitor::acknowledge_and_remember(&apply_toggle_mute(backend, &name, &targets));
+        resolve_channel(backend, &name, &targets)
+    });
+
+    if channel.is_none() {
+        log::warn!("Toggle Mute: No Session Found: {}", session_name);
     }
 
-    let new_volume = volume as f32 / 100.0;
+    return channel.as_ref().map(|channel| channel.to_audio_session(&curve));
+}
 
-    unsafe {
-        let controller = get_audio_controller();
-        let mut sessions;
+/// The mixer channels that show `changed` sessions: the sessions themselves, or the groups they belong to.
+/// Runs on the audio worker.
+pub fn affected_channels(backend: &dyn AudioBackend, changed: &[BackendSession]) -> Vec<AudioSession> {
+    let targets = Targets::load();
+    let mut channels = Vec::new();
+    let mut grouped: Vec<String> = Vec::new();
 
-        if session_name.to_lowercase() == "other" {
-            sessions = controller.get_all_sessions();
-            sessions.retain(|session| !config::get_defined_session_names().contains(&session.get_name().to_lowercase()));
-        } else {
-            sessions = controller.get_all_sessions_with_name(session_name.to_string());
+    for group in &targets.groups {
+        let members = find_sessions(backend, &group.name, &targets);
+        grouped.extend(members.iter().map(|member| member.id.clone()));
+        if members.iter().any(|member| changed.iter().any(|session| session.id == member.id)) {
+            channels.push(Channel::Group(group.name.clone(), members));
         }
+    }
 
-        if sessions.is_empty() {
-            log::warn!("Set Volume: No Session Found: {}", session_name);
-            return None;
-        }
+    channels.extend(
+        changed
+            .iter()
+            .filter(|session| !grouped.contains(&session.id))
+            .cloned()
+            .map(Channel::Session),
+    );
+
+    return channels.iter().map(|channel| channel.to_audio_session(&targets.curve)).collect();
+}
+
+/// Current peak of every mixer channel, keyed by the id the mixer knows it by. A group peaks with its loudest member.
+/// Runs on the audio worker.
+pub fn channel_peaks(backend: &dyn AudioBackend, targets: &Targets) -> Vec<(String, f32)> {
+    let peak = |session: &BackendSession| backend.peak(&session.id).unwrap_or_default();
 
-        for session in sessions {
-            log::info!("Setting {} volume -> {}", session.get_name(), volume);
-            session.set_volume(new_volume);
+    return mixer_channels(backend, targets)
+        .iter()
+        .map(|channel| match channel {
+            Channel::Session(session) => (session.id.clone(), peak(session)),
+            Channel::Group(name, members) => (name.clone(), members.iter().map(peak).fold(0.0, f32::max)),
+        })
+        .collect();
+}
+
+/// Sessions addressed by `session_name`, for jobs already running on the audio worker.
+pub fn resolve_sessions(backend: &dyn AudioBackend, session_name: &str, targets: &Targets) -> Vec<BackendSession> {
+    return find_sessions(backend, session_name, targets);
+}
+
+fn mixer_channels(backend: &dyn AudioBackend, targets: &Targets) -> Vec<Channel> {
+    let mut channels = Vec::new();
+    let mut grouped: Vec<String> = Vec::new();
+
+    for group in &targets.groups {
+        let members = find_sessions(backend, &group.name, targets);
+        grouped.extend(members.iter().map(|member| member.id.clone()));
+        if !members.is_empty() {
+            channels.push(Channel::Group(group.name.clone(), members));
         }
     }
 
-    return get_session(session_name);
+    channels.extend(
+        backend
+            .sessions()
+            .into_iter()
+            .filter(|session| !grouped.contains(&session.id))
+            .map(Channel::Session),
+    );
+
+    return channels;
 }
 
-#[tauri::command]
-pub fn get_session_mute(session_name: &str) -> bool {
-    unsafe {
-        let controller = get_audio_controller();
-        let session = controller.get_session_with_name(session_name.to_string());
+fn resolve_channel(backend: &dyn AudioBackend, session_name: &str, targets: &Targets) -> Option<Channel> {
+    let sessions = find_sessions(backend, session_name, targets);
+    match targets.group(session_name) {
+        Some(group) if !sessions.is_empty() => Some(Channel::Group(group.name.clone(), sessions)),
+        _ => sessions.into_iter().next().map(Channel::Session),
+    }
+}
+
+// Resolve a target to backend sessions. A group expands to the union of its members.
+fn find_sessions(backend: &dyn AudioBackend, session_name: &str, targets: &Targets) -> Vec<BackendSession> {
+    let Some(group) = targets.group(session_name) else {
+        return find_member_sessions(backend, session_name, targets);
+    };
 
-        if session.is_none() {
-            log::error!("Get Mute: No Session Found: {}", session_name);
-            return false;
+    let mut sessions: Vec<BackendSession> = Vec::new();
+    for member in &group.members {
+        for session in find_member_sessions(backend, member, targets) {
+            if !sessions.iter().any(|existing| existing.id == session.id) {
+                sessions.push(session);
+            }
         }
+    }
+    return sessions;
+}
+
+// 'other' expands to every app session that no defined entry or group claims, an instance id picks
+// that one session, and anything else is a config entry or a session name.
+fn find_member_sessions(backend: &dyn AudioBackend, session_name: &str, targets: &Targets) -> Vec<BackendSession> {
+    let mut sessions = backend.sessions();
 
-        return session.unwrap().get_mute();
+    if session_name.eq_ignore_ascii_case(OTHER_SESSION) {
+        let grouped: Vec<String> = targets
+            .groups
+            .iter()
+            .flat_map(|group| &group.members)
+            .filter(|member| !member.eq_ignore_ascii_case(OTHER_SESSION))
+            .flat_map(|member| find_member_sessions(backend, member, targets))
+            .map(|session| session.id)
+            .collect();
+
+        sessions.retain(|session| {
+            session.id != MASTER_SESSION_ID
+                && !grouped.contains(&session.id)
+                && !targets.sessions.iter().any(|matcher| matcher.matches(session))
+        });
+    } else if sessions.iter().any(|session| session.id == session_name) {
+        sessions.retain(|session| session.id == session_name);
+    } else if let Some(matcher) = targets
+        .sessions
+        .iter()
+        .find(|matcher| matcher.name().eq_ignore_ascii_case(session_name))
+    {
+        sessions.retain(|session| matcher.matches(session));
+    } else {
+        sessions.retain(|session| session.name.eq_ignore_ascii_case(session_name));
     }
+
+    return sessions;
 }
 
-#[tauri::command]
-pub fn set_session_mute(session_name: &str, mute: bool) -> bool {
-    unsafe {
-        let controller = get_audio_controller();
-        let mut sessions;
-
-        if session_name.to_lowercase() == "other" {
-            sessions = controller.get_all_sessions();
-            sessions.retain(|session| !config::get_defined_session_names().contains(&session.get_name().to_lowercase()));
-        } else {
-            sessions = controller.get_all_sessions_with_name(session_name.to_string());
-        }
+// Sessions that started since the backend last refreshed aren't known yet, so a miss re-reads it before giving up
+fn refresh_on_miss(backend: &mut dyn AudioBackend, session_name: &str, targets: &Targets) {
+    if find_sessions(backend, session_name, targets).is_empty() {
+        backend.refresh_now();
+    }
+}
 
-        if sessions.is_empty() {
-            log::warn!("Set Mute: No Session Found: {}", session_name);
-            return false;
-        }
+fn apply_session_volume(backend: &mut dyn AudioBackend, session_name: &str, volume: i32, targets: &Targets) -> Vec<BackendSession> {
+    let volume = volume.clamp(0, 100);
+
+    refresh_on_miss(backend, session_name, targets);
+    let sessions = find_sessions(backend, session_name, targets);
+    if sessions.is_empty() {
+        log::warn!("Set Volume: No Session Found: {}", session_name);
+        return Vec::new();
+    }
+
+    let ratios = match targets.group(session_name) {
+        Some(group) if group.mode == GroupMode::Relative => targets.ratios(&group.name, &sessions),
+        _ => HashMap::new(),
+    };
+
+    for session in &sessions {
+        let new_volume = targets.curve.for_session(session).to_scalar(volume);
+        // Members without a known share, like ones that joined while the group was silent, go to the full level
+        let session_volume = new_volume * ratios.get(&session.id).copied().unwrap_or(1.0);
+        log::info!("Setting {} ({}) volume -> {}", session.name, session.pid, session_volume);
+        backend.set_volume(&session.id, session_volume);
+    }
+
+    return find_sessions(backend, session_name, targets);
+}
+
+fn apply_session_mute(backend: &mut dyn AudioBackend, session_name: &str, mute: bool, targets: &Targets) -> bool {
+    refresh_on_miss(backend, session_name, targets);
+    let sessions = find_sessions(backend, session_name, targets);
+    if sessions.is_empty() {
+        log::warn!("Set Mute: No Session Found: {}", session_name);
+        return false;
+    }
+
+    for session in &sessions {
+        backend.set_mute(&session.id, mute);
+        log::info!("Setting {} ({}) mute -> {}", session.name, session.pid, mute);
+    }
 
-        for session in sessions {
-            session.set_mute(mute);
-            log::info!("Setting {} mute -> {}", session.get_name(), mute);
+    return mute;
+}
+
+fn apply_volume_step(backend: &mut dyn AudioBackend, session_name: &str, delta: i32, targets: &Targets) -> Vec<BackendSession> {
+    refresh_on_miss(backend, session_name, targets);
+    let current = match resolve_channel(backend, session_name, targets) {
+        Some(channel) => channel.position(&targets.curve),
+        None => {
+            log::warn!("Step Volume: No Session Found: {}", session_name);
+            return Vec::new();
         }
+    };
 
-        return mute;
+    apply_session_volume(backend, session_name, current + delta, targets);
+    if delta > 0 {
+        apply_session_mute(backend, session_name, false, targets);
     }
+
+    return find_sessions(backend, session_name, targets);
 }
 
-pub fn toggle_session_mute(session_name: &str) -> AudioSession {
-    log::info!("TOGGLE MUTE: {}", session_name);
-    let mute = get_session_mute(session_name);
-    set_session_mute(session_name, !mute);
+fn apply_toggle_mute(backend: &mut dyn AudioBackend, session_name: &str, targets: &Targets) -> Vec<BackendSession> {
+    refresh_on_miss(backend, session_name, targets);
+    let mute = resolve_channel(backend, session_name, targets).is_some_and(|channel| channel.mute());
+    apply_session_mute(backend, session_name, !mute, targets);
+
+    return find_sessions(backend, session_name, targets);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CurveKind, SessionConfig};
+    use crate::services::mock_audio_backend::MockAudioBackend;
+
+    fn targets(sessions: Vec<SessionConfig>, groups: Vec<GroupConfig>) -> Targets {
+        Targets::new(&Config {
+            sessions,
+            groups,
+            ..Default::default()
+        })
+    }
+
+    fn defined() -> Targets {
+        targets(
+            vec![
+                SessionConfig::new("master"),
+                SessionConfig::new("chrome"),
+                SessionConfig::new("discord"),
+            ],
+            Vec::new(),
+        )
+    }
+
+    fn group(name: &str, members: &[&str], mode: GroupMode) -> GroupConfig {
+        GroupConfig {
+            name: name.to_string(),
+            members: members.iter().map(|member| member.to_string()).collect(),
+            mode,
+            keybinds: None,
+            step: None,
+        }
+    }
+
+    fn games(mode: GroupMode) -> Targets {
+        targets(
+            vec![SessionConfig::new("master")],
+            vec![group("Games", &["steam", "minecraft"], mode)],
+        )
+    }
+
+    fn game_backend() -> MockAudioBackend {
+        MockAudioBackend::new()
+            .with_session("master", 0.5, false)
+            .with_session("steam", 0.8, false)
+            .with_session("minecraft", 0.4, false)
+            .with_session("Spotify", 0.6, false)
+    }
+
+    fn backend() -> MockAudioBackend {
+        MockAudioBackend::new()
+            .with_session("master", 0.5, false)
+            .with_session("chrome", 0.4, false)
+            .with_session("chrome", 0.4, false)
+            .with_session("Spotify", 0.8, false)
+            .with_session("steam", 0.3, true)
+    }
+
+    fn volume(backend: &MockAudioBackend, name: &str) -> Option<f32> {
+        backend.named(name).map(|session| session.volume)
+    }
+
+    fn mute(backend: &MockAudioBackend, name: &str) -> Option<bool> {
+        backend.named(name).map(|session| session.mute)
+    }
+
+    fn names(sessions: &[BackendSession]) -> Vec<&str> {
+        sessions.iter().map(|session| session.name.as_str()).collect()
+    }
+
+    #[test]
+    fn set_volume_updates_every_session_with_the_name() {
+        let mut backend = backend();
+
+        let sessions = apply_session_volume(&mut backend, "chrome", 70, &defined());
+
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().all(|session| session.volume == 0.7));
+    }
+
+    #[test]
+    fn set_volume_clamps_to_valid_range() {
+        let mut backend = backend();
+
+        apply_session_volume(&mut backend, "master", 150, &defined());
+        assert_eq!(volume(&backend, "master"), Some(1.0));
+
+        apply_session_volume(&mut backend, "master", -20, &defined());
+        assert_eq!(volume(&backend, "master"), Some(0.0));
+    }
+
+    #[test]
+    fn set_volume_on_missing_session_returns_nothing() {
+        let mut backend = backend();
+
+        assert!(apply_session_volume(&mut backend, "teams", 50, &defined()).is_empty());
+    }
+
+    #[test]
+    fn missing_session_refreshes_the_backend_before_giving_up() {
+        let mut backend = backend();
+        backend.add_pending_session("teams", 0.5, false);
+
+        let sessions = apply_session_volume(&mut backend, "teams", 30, &defined());
+
+        assert_eq!(names(&sessions), vec!["teams"]);
+        assert_eq!(volume(&backend, "teams"), Some(0.3));
+    }
+
+    #[test]
+    fn other_contains_only_undefined_sessions() {
+        let backend = backend();
+
+        let sessions = find_sessions(&backend, "Other", &defined());
+
+        assert_eq!(names(&sessions), vec!["Spotify", "steam"]);
+    }
+
+    #[test]
+    fn set_volume_on_other_leaves_defined_sessions_alone() {
+        let mut backend = backend();
+
+        apply_session_volume(&mut backend, "other", 10, &defined());
+
+        assert_eq!(volume(&backend, "Spotify"), Some(0.1));
+        assert_eq!(volume(&backend, "steam"), Some(0.1));
+        assert_eq!(volume(&backend, "master"), Some(0.5));
+        assert_eq!(volume(&backend, "chrome"), Some(0.4));
+    }
+
+    #[test]
+    fn toggle_mute_flips_current_state() {
+        let mut backend = backend();
+
+        let sessions = apply_toggle_mute(&mut backend, "chrome", &defined());
+        assert!(sessions.iter().all(|session| session.mute));
+
+        let sessions = apply_toggle_mute(&mut backend, "chrome", &defined());
+        assert!(sessions.iter().all(|session| !session.mute));
+    }
+
+    #[test]
+    fn toggle_mute_on_other_follows_first_session() {
+        let mut backend = backend();
+
+        apply_toggle_mute(&mut backend, "other", &defined());
+
+        assert_eq!(mute(&backend, "Spotify"), Some(true));
+        assert_eq!(mute(&backend, "steam"), Some(true));
+        assert_eq!(mute(&backend, "master"), Some(false));
+    }
+
+    #[test]
+    fn volume_step_up_unmutes_and_down_does_not() {
+        let mut backend = backend();
+
+        let sessions = apply_volume_step(&mut backend, "steam", -2, &defined());
+        assert_eq!(sessions[0].volume, 0.28);
+        assert!(sessions[0].mute);
+
+        let sessions = apply_volume_step(&mut backend, "steam", 2, &defined());
+        assert_eq!(sessions[0].volume, 0.3);
+        assert!(!sessions[0].mute);
+    }
 
-    let audio_session = get_sessions(session_name).into_iter().next();
+    #[test]
+    fn session_id_targets_a_single_instance() {
+        let mut backend = MockAudioBackend::new();
+        let first = backend.add_session("chrome", 0.4, false);
+        let second = backend.add_session("chrome", 0.4, false);
 
-    return audio_session.expect("No Session Found");
+        let sessions = apply_session_volume(&mut backend, &second, 90, &defined());
+
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(backend.session(&first).unwrap().volume, 0.4);
+        assert_eq!(backend.session(&second).unwrap().volume, 0.9);
+    }
+
+    #[test]
+    fn colliding_names_are_told_apart_by_exe() {
+        let mut backend = MockAudioBackend::new();
+        let steam = backend.add_process("game", "C:\\Steam\\Game\\game.exe", 0.5, false);
+        let epic = backend.add_process("game", "D:\\Epic\\Game\\game.exe", 0.5, false);
+
+        let mut steam_game = SessionConfig::new("Steam game");
+        steam_game.exe = Some("C:\\Steam\\Game\\game.exe".to_string());
+        let defined = targets(vec![steam_game], Vec::new());
+
+        apply_session_volume(&mut backend, "steam game", 20, &defined);
+        assert_eq!(backend.session(&steam).unwrap().volume, 0.2);
+        assert_eq!(backend.session(&epic).unwrap().volume, 0.5);
+
+        // The other install isn't claimed by any entry
+        let ids: Vec<String> = find_sessions(&backend, "other", &defined)
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+        assert_eq!(ids, vec![epic]);
+    }
+
+    #[test]
+    fn one_entry_moves_every_browser() {
+        let mut backend = MockAudioBackend::new()
+            .with_session("chrome", 0.5, false)
+            .with_session("msedge", 0.5, false)
+            .with_session("firefox", 0.5, false)
+            .with_session("steam", 0.5, false);
+        let mut browsers = SessionConfig::new("Browsers");
+        browsers.aliases = Some(vec!["chrome".to_string(), "msedge".to_string(), "firefox".to_string()]);
+        let defined = targets(vec![browsers], Vec::new());
+
+        let sessions = apply_session_volume(&mut backend, "browsers", 30, &defined);
+
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(volume(&backend, "steam"), Some(0.5));
+        assert_eq!(names(&find_sessions(&backend, "other", &defined)), vec!["steam"]);
+    }
+
+    #[test]
+    fn title_rule_follows_the_window() {
+        let mut backend = MockAudioBackend::new();
+        let chrome = backend.add_session("chrome", 0.5, false);
+        let mut music = SessionConfig::new("Music");
+        music.title = Some("YouTube Music".to_string());
+        let defined = targets(vec![music], Vec::new());
+
+        assert!(find_sessions(&backend, "music", &defined).is_empty());
+
+        backend.set_title(&chrome, "Song - YouTube Music");
+        assert_eq!(find_sessions(&backend, "music", &defined).len(), 1);
+    }
+
+    #[test]
+    fn relative_group_keeps_the_mix_between_members() {
+        let mut backend = game_backend();
+
+        apply_session_volume(&mut backend, "games", 40, &games(GroupMode::Relative));
+
+        assert_eq!(volume(&backend, "steam"), Some(0.4));
+        assert_eq!(volume(&backend, "minecraft"), Some(0.2));
+        assert_eq!(volume(&backend, "Spotify"), Some(0.6));
+    }
+
+    #[test]
+    fn relative_group_comes_back_from_zero_with_its_mix() {
+        let mut backend = game_backend();
+        let targets = games(GroupMode::Relative);
+
+        apply_session_volume(&mut backend, "games", 0, &targets);
+        assert_eq!(volume(&backend, "steam"), Some(0.0));
+        assert_eq!(volume(&backend, "minecraft"), Some(0.0));
+
+        apply_session_volume(&mut backend, "games", 50, &targets);
+
+        assert_eq!(volume(&backend, "steam"), Some(0.5));
+        assert_eq!(volume(&backend, "minecraft"), Some(0.25));
+    }
+
+    #[test]
+    fn absolute_group_sets_every_member() {
+        let mut backend = game_backend();
+
+        apply_session_volume(&mut backend, "Games", 40, &games(GroupMode::Absolute));
+
+        assert_eq!(volume(&backend, "steam"), Some(0.4));
+        assert_eq!(volume(&backend, "minecraft"), Some(0.4));
+    }
+
+    #[test]
+    fn group_steps_from_its_loudest_member() {
+        let mut backend = game_backend();
+        let targets = games(GroupMode::Relative);
+
+        apply_volume_step(&mut backend, "games", -40, &targets);
+
+        let channel = resolve_channel(&backend, "games", &targets).unwrap();
+        assert_eq!((channel.volume() * 100.0).round(), 40.0);
+        assert_eq!(volume(&backend, "minecraft"), Some(0.2));
+    }
+
+    #[test]
+    fn group_mute_toggles_all_members_together() {
+        let mut backend = game_backend();
+        let targets = games(GroupMode::Relative);
+        apply_session_mute(&mut backend, "steam", true, &targets);
+
+        // Only partly muted, so the group counts as unmuted and the toggle mutes everything
+        apply_toggle_mute(&mut backend, "games", &targets);
+        assert_eq!(mute(&backend, "steam"), Some(true));
+        assert_eq!(mute(&backend, "minecraft"), Some(true));
+        assert!(resolve_channel(&backend, "games", &targets).unwrap().mute());
+
+        apply_toggle_mute(&mut backend, "games", &targets);
+        assert_eq!(mute(&backend, "steam"), Some(false));
+        assert_eq!(mute(&backend, "minecraft"), Some(false));
+    }
+
+    #[test]
+    fn grouped_sessions_collapse_into_one_mixer_channel() {
+        let backend = game_backend();
+        let targets = games(GroupMode::Relative);
+
+        let channels: Vec<String> = mixer_channels(&backend, &targets)
+            .iter()
+            .map(|channel| match channel {
+                Channel::Session(session) => session.name.clone(),
+                Channel::Group(name, _) => name.clone(),
+            })
+            .collect();
+
+        assert_eq!(channels, vec!["Games", "master", "Spotify"]);
+        assert_eq!(names(&find_sessions(&backend, "other", &targets)), vec!["Spotify"]);
+    }
+
+    #[test]
+    fn group_peaks_with_its_loudest_member() {
+        let mut backend = game_backend();
+        let id = |name: &str| backend.named(name).unwrap().id.clone();
+        let (steam, minecraft, spotify) = (id("steam"), id("minecraft"), id("Spotify"));
+        backend.set_peak(&steam, 0.3);
+        backend.set_peak(&minecraft, 0.7);
+        backend.set_peak(&spotify, 0.2);
+
+        let peaks = channel_peaks(&backend, &games(GroupMode::Relative));
+
+        assert_eq!(peaks, vec![("Games".to_string(), 0.7), ("master".to_string(), 0.0), (spotify, 0.2)]);
+    }
+
+    #[test]
+    fn curve_sits_between_the_slider_and_the_backend() {
+        let mut backend = backend();
+        let mut config = Config {
+            sessions: vec![SessionConfig::new("master"), SessionConfig::new("Spotify")],
+            ..Default::default()
+        };
+        config.volume.curve = CurveKind::Logarithmic;
+        let targets = Targets::new(&config);
+
+        apply_session_volume(&mut backend, "Spotify", 50, &targets);
+        apply_volume_step(&mut backend, "Spotify", 25, &targets);
+        assert_eq!((volume(&backend, "Spotify").unwrap() * 1000.0).round(), 316.0);
+        assert_eq!(resolve_channel(&backend, "Spotify", &targets).unwrap().position(&targets.curve), 75);
+
+        // Master is the endpoint level, which Windows already tapers
+        apply_session_volume(&mut backend, "master", 50, &targets);
+        assert_eq!(volume(&backend, "master"), Some(0.5));
+    }
 }
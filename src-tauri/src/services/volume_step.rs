@@ -0,0 +1,149 @@
+use std::time::{Duration, Instant};
+
+use crate::config::StepConfig;
+
+// Presses closer together than this are key repeat, which fires every 30-50ms once it starts. Even quick taps come
+// further apart, so they never accelerate.
+const REPEAT_GAP: Duration = Duration::from_millis(100);
+
+// Repeats that still move by the plain step, so a quick double tap stays precise
+const REPEATS_BEFORE_ACCELERATION: u32 = 2;
+
+/// How far one volume_up or volume_down binding moves its channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub size: i32,
+    pub max: i32,
+}
+
+impl Step {
+    /// The keybind's step wins over its session's or group's, which wins over the global one.
+    pub fn resolve(config: &StepConfig, owner: Option<i32>, keybind: Option<i32>) -> Self {
+        let size = keybind.or(owner).unwrap_or(config.size).clamp(1, 100);
+        let max = config.max.unwrap_or(size).clamp(size, 100);
+        Step { size, max }
+    }
+
+    /// Step for a press that comes `repeats` presses into a held key. Past the first couple of repeats each one
+    /// moves a point further, up to max.
+    pub fn at(&self, repeats: u32) -> i32 {
+        let extra = repeats.saturating_sub(REPEATS_BEFORE_ACCELERATION).min(100) as i32;
+        (self.size + extra).min(self.max)
+    }
+}
+
+/// Counts how many presses of one key came in a row.
+#[derive(Debug, Default)]
+pub struct Repeats {
+    last: Option<Instant>,
+    count: u32,
+}
+
+impl Repeats {
+    /// Record a press, returning how many presses came right before it. A tap returns 0, and so does the first
+    /// repeat after the delay before key repeat starts.
+    pub fn press(&mut self, now: Instant) -> u32 {
+        let repeating = self.last.is_some_and(|last| now.saturating_duration_since(last) < REPEAT_GAP);
+        self.count = if repeating { self.count + 1 } else { 0 };
+        self.last = Some(now);
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Typical Windows key repeat: a 500ms delay, then a press every 33ms
+    fn hold(repeats: &mut Repeats, start: Instant, presses: u32) -> Vec<u32> {
+        (0..presses)
+            .map(|press| {
+                let at = match press {
+                    0 => start,
+                    _ => start + Duration::from_millis(500 + 33 * (press as u64 - 1)),
+                };
+                repeats.press(at)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keybind_step_wins_over_session_and_global() {
+        let config = StepConfig { size: 2, max: Some(10) };
+
+        assert_eq!(Step::resolve(&config, None, None), Step { size: 2, max: 10 });
+        assert_eq!(Step::resolve(&config, Some(5), None), Step { size: 5, max: 10 });
+        assert_eq!(Step::resolve(&config, Some(5), Some(1)), Step { size: 1, max: 10 });
+        // A step above max is never slowed down, and nothing goes past 100
+        assert_eq!(Step::resolve(&config, Some(20), None), Step { size: 20, max: 20 });
+        assert_eq!(
+            Step::resolve(&StepConfig { size: 500, max: None }, None, None),
+            Step { size: 100, max: 100 }
+        );
+    }
+
+    #[test]
+    fn taps_stay_precise() {
+        let step = Step { size: 2, max: 10 };
+        let mut repeats = Repeats::default();
+        let start = Instant::now();
+
+        // Tapping as fast as a finger goes
+        let taps: Vec<i32> = (0..10)
+            .map(|tap| step.at(repeats.press(start + Duration::from_millis(200 * tap))))
+            .collect();
+
+        assert_eq!(taps, vec![2; 10]);
+        assert_eq!(step.at(1), 2);
+        assert_eq!(step.at(2), 2);
+    }
+
+    #[test]
+    fn holding_accelerates_up_to_max() {
+        let step = Step { size: 2, max: 6 };
+        let mut repeats = Repeats::default();
+
+        let steps: Vec<i32> = hold(&mut repeats, Instant::now(), 9)
+            .into_iter()
+            .map(|count| step.at(count))
+            .collect();
+
+        assert_eq!(steps, vec![2, 2, 2, 2, 3, 4, 5, 6, 6]);
+    }
+
+    #[test]
+    fn long_press_sweeps_the_whole_range_quickly() {
+        let step = Step { size: 2, max: 10 };
+        let mut repeats = Repeats::default();
+
+        let presses = hold(&mut repeats, Instant::now(), 100)
+            .into_iter()
+            .scan(0, |volume, count| {
+                *volume += step.at(count);
+                Some(*volume)
+            })
+            .position(|volume| volume >= 100)
+            .unwrap()
+            + 1;
+
+        // Under a second of holding, where a fixed step of 2 would take 50 presses
+        assert!(presses <= 16, "took {} presses", presses);
+    }
+
+    #[test]
+    fn no_max_means_no_acceleration() {
+        let step = Step::resolve(&StepConfig { size: 3, max: None }, None, None);
+        let mut repeats = Repeats::default();
+
+        assert!(hold(&mut repeats, Instant::now(), 30).into_iter().all(|count| step.at(count) == 3));
+    }
+
+    #[test]
+    fn letting_go_starts_over() {
+        let mut repeats = Repeats::default();
+        let start = Instant::now();
+
+        assert_eq!(hold(&mut repeats, start, 6), vec![0, 0, 1, 2, 3, 4]);
+        assert_eq!(repeats.press(start + Duration::from_secs(5)), 0);
+    }
+}
@@ -4,6 +4,7 @@ use std::{
     time::{Duration, Instant},
 };
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+#[cfg(windows)]
 use windows::Win32::{
     Foundation::RECT,
     UI::WindowsAndMessaging::{GetSystemMetrics, SystemParametersInfoA, SM_CYSCREEN, SPI_GETWORKAREA, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS},
@@ -43,8 +44,8 @@ pub fn create_mixer(app: AppHandle) -> WebviewWindow {
     let window_clone = window.clone();
     let last_focus_time_clone = Arc::clone(&last_focus_time);
     let app_handle = app.clone();
-    window.on_window_event(move |event| match event {
-        tauri::WindowEvent::Focused(is_focused) => {
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Focused(is_focused) = event {
             if *is_focused {
                 *last_focus_time_clone.lock().unwrap() = Instant::now();
             } else {
@@ -63,7 +64,6 @@ pub fn create_mixer(app: AppHandle) -> WebviewWindow {
                 }
             }
         }
-        _ => {}
     });
 
     return window;
@@ -124,6 +124,13 @@ pub fn get_window(app: AppHandle, window_label: &str) -> Option<WebviewWindow> {
     app.get_webview_window(window_label)
 }
 
+#[cfg(not(windows))]
+#[tauri::command]
+pub fn get_taskbar_height() -> i32 {
+    0
+}
+
+#[cfg(windows)]
 #[tauri::command]
 pub fn get_taskbar_height() -> i32 {
     unsafe {
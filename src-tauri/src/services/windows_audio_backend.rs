@@ -0,0 +1,524 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use windows::core::{Interface, PWSTR};
+use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+use windows::Win32::Foundation::S_OK;
+use windows::Win32::Media::Audio::Endpoints::{IAudioEndpointVolume, IAudioMeterInformation};
+use windows::Win32::Media::Audio::{
+    eCapture, eCommunications, eMultimedia, eRender, AudioSessionStateExpired, EDataFlow, IAudioSessionControl, IAudioSessionControl2,
+    IAudioSessionManager2, IMMDevice, IMMDeviceEnumerator, ISimpleAudioVolume, MMDeviceEnumerator, DEVICE_STATE_ACTIVE,
+};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_APARTMENTTHREADED, STGM_READ};
+
+use crate::models::audio_device::DeviceKind;
+
+use super::audio_backend::{AudioBackend, BackendDevice, BackendSession, TitleFilter};
+use super::policy_config;
+use super::process_service::{self, ProcessWindow};
+
+// Sessions come and go rarely compared to how often hotkeys fire
+const SESSION_REFRESH_INTERVAL: Duration = Duration::from_millis(1000);
+
+const MASTER_SESSION: &str = "master";
+const SYSTEM_SOUNDS_SESSION: &str = "system";
+
+/// Core Audio backend: sessions of the default render endpoint plus every active render and capture endpoint.
+/// Must stay on the thread that created it.
+pub struct WindowsAudioBackend {
+    enumerator: Option<IMMDeviceEnumerator>,
+    master: Option<IAudioEndpointVolume>,
+    master_meter: Option<IAudioMeterInformation>,
+    sessions: Vec<WindowsSession>,
+    devices: Vec<WindowsDevice>,
+    default_device_id: Option<String>,
+    default_input_id: Option<String>,
+    // Looked up once per process rather than on every refresh, for sessions and windows alike
+    exe_paths: HashMap<u32, Option<String>>,
+    needs_title: TitleFilter,
+    last_refresh: Instant,
+}
+
+struct WindowsDevice {
+    id: String,
+    name: String,
+    kind: DeviceKind,
+    volume: IAudioEndpointVolume,
+    meter: IAudioMeterInformation,
+}
+
+struct WindowsSession {
+    id: String,
+    name: String,
+    pid: u32,
+    exe_path: Option<String>,
+    title: Option<String>,
+    device_id: String,
+    volume: ISimpleAudioVolume,
+    meter: IAudioMeterInformation,
+}
+
+impl WindowsAudioBackend {
+    pub fn new() -> Self {
+        unsafe {
+            if let Err(e) = CoInitializeEx(None, COINIT_APARTMENTTHREADED).ok() {
+                log::error!("Failed to initialize COM: {}", e);
+            }
+        }
+
+        let mut backend = WindowsAudioBackend {
+            enumerator: None,
+            master: None,
+            master_meter: None,
+            sessions: Vec::new(),
+            devices: Vec::new(),
+            default_device_id: None,
+            default_input_id: None,
+            exe_paths: HashMap::new(),
+            needs_title: Box::new(|_| false),
+            last_refresh: Instant::now(),
+        };
+        backend.reload();
+        backend
+    }
+
+    fn reload(&mut self) {
+        if let Err(e) = unsafe { self.load_sessions() } {
+            log::error!("Failed to enumerate audio sessions: {}", e);
+            self.master = None;
+            self.master_meter = None;
+            self.sessions.clear();
+        }
+        if let Err(e) = unsafe { self.load_devices() } {
+            log::error!("Failed to enumerate audio devices: {}", e);
+            self.devices.clear();
+        }
+        self.last_refresh = Instant::now();
+    }
+
+    unsafe fn enumerator(&mut self) -> windows::core::Result<IMMDeviceEnumerator> {
+        if let Some(enumerator) = &self.enumerator {
+            return Ok(enumerator.clone());
+        }
+
+        let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+        self.enumerator = Some(enumerator.clone());
+        Ok(enumerator)
+    }
+
+    unsafe fn load_sessions(&mut self) -> windows::core::Result<()> {
+        let enumerator = self.enumerator()?;
+
+        // Re-resolved every time so a change of default device is picked up
+        let device = enumerator.GetDefaultAudioEndpoint(eRender, eMultimedia)?;
+        let default_device_id = take_string(device.GetId()?);
+        if self.master.is_none() || self.default_device_id.as_deref() != Some(default_device_id.as_str()) {
+            self.master = Some(device.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None)?);
+            self.master_meter = Some(device.Activate::<IAudioMeterInformation>(CLSCTX_ALL, None)?);
+        }
+
+        // Apps routed to another output have their sessions on that device
+        let endpoints = enumerator.EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)?;
+        let mut known: HashMap<String, WindowsSession> = self.sessions.drain(..).map(|session| (session.id.clone(), session)).collect();
+        let mut sessions = Vec::new();
+        for index in 0..endpoints.GetCount()? {
+            let endpoint = endpoints.Item(index)?;
+            let device_id = take_string(endpoint.GetId()?);
+            match self.load_endpoint_sessions(&endpoint, &device_id, &mut known) {
+                // System sounds only ever play on the default device
+                Ok(found) => sessions.extend(
+                    found
+                        .into_iter()
+                        .filter(|session| session.name != SYSTEM_SOUNDS_SESSION || device_id == default_device_id),
+                ),
+                Err(e) => log::debug!("Skipping sessions of audio device {}: {}", device_id, e),
+            }
+        }
+
+        self.default_device_id = Some(default_device_id);
+        self.sessions = sessions;
+        self.load_titles();
+        Ok(())
+    }
+
+    // Window titles change all the time, so they're read on every refresh, but only for sessions the title filter
+    // wants. Without such sessions the windows aren't enumerated at all.
+    fn load_titles(&mut self) {
+        let wanted: Vec<usize> = (0..self.sessions.len())
+            .filter(|index| (self.needs_title)(&self.backend_session(&self.sessions[*index])))
+            .collect();
+
+        let windows = if wanted.is_empty() {
+            Vec::new()
+        } else {
+            process_service::get_windows()
+        };
+        for session in &mut self.sessions {
+            session.title = None;
+        }
+        for index in wanted {
+            let (pid, exe_path) = (self.sessions[index].pid, self.sessions[index].exe_path.clone());
+            self.sessions[index].title = self.window_title(pid, exe_path.as_deref(), &windows);
+        }
+
+        // Pids get reused, so only processes still around keep their path
+        let live: HashSet<u32> = self
+            .sessions
+            .iter()
+            .map(|session| session.pid)
+            .chain(windows.iter().map(|window| window.pid))
+            .collect();
+        self.exe_paths.retain(|pid, _| live.contains(pid));
+    }
+
+    // Browsers play audio from a helper process without a window, so fall back to a window of the same executable
+    fn window_title(&mut self, pid: u32, exe_path: Option<&str>, windows: &[ProcessWindow]) -> Option<String> {
+        if let Some(window) = windows.iter().find(|window| window.pid == pid) {
+            return Some(window.title.clone());
+        }
+        let exe_path = exe_path?;
+        windows
+            .iter()
+            .find(|window| self.exe_path(window.pid).as_deref() == Some(exe_path))
+            .map(|window| window.title.clone())
+    }
+
+    unsafe fn load_endpoint_sessions(
+        &mut self,
+        device: &IMMDevice,
+        device_id: &str,
+        known: &mut HashMap<String, WindowsSession>,
+    ) -> windows::core::Result<Vec<WindowsSession>> {
+        let manager = device.Activate::<IAudioSessionManager2>(CLSCTX_ALL, None)?;
+        let session_list = manager.GetSessionEnumerator()?;
+
+        let mut sessions = Vec::new();
+        for index in 0..session_list.GetCount()? {
+            let control = session_list.GetSession(index)?;
+            if control.GetState()? == AudioSessionStateExpired {
+                continue;
+            }
+            match self.load_session(&control, device_id, known) {
+                Ok(session) => sessions.push(session),
+                Err(e) => log::debug!("Skipping audio session {}: {}", index, e),
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    unsafe fn load_session(
+        &mut self,
+        control: &IAudioSessionControl,
+        device_id: &str,
+        known: &mut HashMap<String, WindowsSession>,
+    ) -> windows::core::Result<WindowsSession> {
+        let control2: IAudioSessionControl2 = control.cast()?;
+        let id = take_string(control2.GetSessionInstanceIdentifier()?);
+
+        // Sessions seen before keep their COM objects
+        if let Some(session) = known.remove(&id) {
+            return Ok(session);
+        }
+
+        let pid = control2.GetProcessId()?;
+        let exe_path = self.exe_path(pid);
+
+        let name = if control2.IsSystemSoundsSession() == S_OK {
+            SYSTEM_SOUNDS_SESSION.to_string()
+        } else {
+            exe_path
+                .as_deref()
+                .and_then(|path| Path::new(path).file_stem())
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("pid {}", pid))
+        };
+
+        Ok(WindowsSession {
+            id,
+            name,
+            pid,
+            title: None,
+            exe_path,
+            device_id: device_id.to_string(),
+            volume: control.cast()?,
+            meter: control.cast()?,
+        })
+    }
+
+    fn exe_path(&mut self, pid: u32) -> Option<String> {
+        self.exe_paths
+            .entry(pid)
+            .or_insert_with(|| process_service::get_exe_path(pid))
+            .clone()
+    }
+
+    unsafe fn load_devices(&mut self) -> windows::core::Result<()> {
+        let enumerator = self.enumerator()?;
+
+        // The communications default is the mic that voice chat picks up, so that is the one mic mute acts on. Not
+        // having a microphone at all is normal, so this is not an error.
+        self.default_input_id = enumerator
+            .GetDefaultAudioEndpoint(eCapture, eCommunications)
+            .and_then(|device| device.GetId())
+            .map(|id| take_string(id))
+            .ok();
+
+        let mut known: HashMap<String, WindowsDevice> = self.devices.drain(..).map(|device| (device.id.clone(), device)).collect();
+        let mut devices = Vec::new();
+        devices.extend(load_endpoints(&enumerator, eRender, DeviceKind::Output, &mut known)?);
+        // Outputs are still worth having when recording devices can't be listed
+        match load_endpoints(&enumerator, eCapture, DeviceKind::Input, &mut known) {
+            Ok(inputs) => devices.extend(inputs),
+            Err(e) => log::error!("Failed to enumerate recording devices: {}", e),
+        }
+
+        self.devices = devices;
+        Ok(())
+    }
+
+    fn backend_session(&self, session: &WindowsSession) -> BackendSession {
+        unsafe {
+            BackendSession {
+                id: session.id.clone(),
+                name: session.name.clone(),
+                pid: session.pid,
+                exe_path: session.exe_path.clone(),
+                title: session.title.clone(),
+                device_id: Some(session.device_id.clone()),
+                volume: session.volume.GetMasterVolume().unwrap_or_default(),
+                mute: session.volume.GetMute().map(|mute| mute.as_bool()).unwrap_or_default(),
+            }
+        }
+    }
+
+    fn find(&self, session_id: &str) -> Option<&WindowsSession> {
+        self.sessions.iter().find(|session| session.id == session_id)
+    }
+
+    fn find_device(&self, device_id: &str) -> Option<&WindowsDevice> {
+        self.devices.iter().find(|device| device.id == device_id)
+    }
+
+    fn default_id(&self, kind: DeviceKind) -> Option<&str> {
+        match kind {
+            DeviceKind::Output => self.default_device_id.as_deref(),
+            DeviceKind::Input => self.default_input_id.as_deref(),
+        }
+    }
+}
+
+unsafe fn load_endpoints(
+    enumerator: &IMMDeviceEnumerator,
+    flow: EDataFlow,
+    kind: DeviceKind,
+    known: &mut HashMap<String, WindowsDevice>,
+) -> windows::core::Result<Vec<WindowsDevice>> {
+    let collection = enumerator.EnumAudioEndpoints(flow, DEVICE_STATE_ACTIVE)?;
+
+    let mut devices = Vec::new();
+    for index in 0..collection.GetCount()? {
+        match load_device(&collection.Item(index)?, kind, known) {
+            Ok(device) => devices.push(device),
+            Err(e) => log::debug!("Skipping audio device {}: {}", index, e),
+        }
+    }
+
+    Ok(devices)
+}
+
+unsafe fn load_device(
+    device: &IMMDevice,
+    kind: DeviceKind,
+    known: &mut HashMap<String, WindowsDevice>,
+) -> windows::core::Result<WindowsDevice> {
+    let id = take_string(device.GetId()?);
+    // Devices seen before keep their COM objects
+    if let Some(device) = known.remove(&id) {
+        return Ok(device);
+    }
+
+    let name = device
+        .OpenPropertyStore(STGM_READ)?
+        .GetValue(&PKEY_Device_FriendlyName)?
+        .to_string();
+
+    Ok(WindowsDevice {
+        id,
+        name,
+        kind,
+        volume: device.Activate::<IAudioEndpointVolume>(CLSCTX_ALL, None)?,
+        meter: device.Activate::<IAudioMeterInformation>(CLSCTX_ALL, None)?,
+    })
+}
+
+// Strings returned by Core Audio are allocated with CoTaskMemAlloc and owned by the caller
+unsafe fn take_string(value: PWSTR) -> String {
+    let text = value.to_string().unwrap_or_default();
+    CoTaskMemFree(Some(value.0 as *const _));
+    text
+}
+
+impl AudioBackend for WindowsAudioBackend {
+    fn refresh(&mut self) {
+        if self.last_refresh.elapsed() < SESSION_REFRESH_INTERVAL {
+            return;
+        }
+
+        self.reload();
+    }
+
+    fn refresh_now(&mut self) {
+        self.reload();
+    }
+
+    fn set_title_filter(&mut self, needs_title: TitleFilter) {
+        self.needs_title = needs_title;
+    }
+
+    fn sessions(&self) -> Vec<BackendSession> {
+        let mut sessions = Vec::new();
+
+        if let Some(master) = &self.master {
+            unsafe {
+                sessions.push(BackendSession {
+                    id: MASTER_SESSION.to_string(),
+                    name: MASTER_SESSION.to_string(),
+                    pid: 0,
+                    exe_path: None,
+                    title: None,
+                    device_id: self.default_device_id.clone(),
+                    volume: master.GetMasterVolumeLevelScalar().unwrap_or_default(),
+                    mute: master.GetMute().map(|mute| mute.as_bool()).unwrap_or_default(),
+                });
+            }
+        }
+
+        sessions.extend(self.sessions.iter().map(|session| self.backend_session(session)));
+        sessions
+    }
+
+    fn set_volume(&mut self, session_id: &str, volume: f32) -> bool {
+        let result = unsafe {
+            if session_id == MASTER_SESSION {
+                self.master
+                    .as_ref()
+                    .map(|master| master.SetMasterVolumeLevelScalar(volume, std::ptr::null()))
+            } else {
+                self.find(session_id)
+                    .map(|session| session.volume.SetMasterVolume(volume, std::ptr::null()))
+            }
+        };
+
+        log_result(result, "volume", session_id)
+    }
+
+    fn devices(&self) -> Vec<BackendDevice> {
+        self.devices
+            .iter()
+            .map(|device| unsafe {
+                BackendDevice {
+                    id: device.id.clone(),
+                    name: device.name.clone(),
+                    kind: device.kind,
+                    volume: device.volume.GetMasterVolumeLevelScalar().unwrap_or_default(),
+                    mute: device.volume.GetMute().map(|mute| mute.as_bool()).unwrap_or_default(),
+                    is_default: self.default_id(device.kind) == Some(device.id.as_str()),
+                }
+            })
+            .collect()
+    }
+
+    fn set_device_volume(&mut self, device_id: &str, volume: f32) -> bool {
+        let result = unsafe {
+            self.find_device(device_id)
+                .map(|device| device.volume.SetMasterVolumeLevelScalar(volume, std::ptr::null()))
+        };
+        log_result(result, "volume", device_id)
+    }
+
+    fn set_device_mute(&mut self, device_id: &str, mute: bool) -> bool {
+        let result = unsafe {
+            self.find_device(device_id)
+                .map(|device| device.volume.SetMute(mute, std::ptr::null()))
+        };
+        log_result(result, "mute", device_id)
+    }
+
+    fn device_peak(&self, device_id: &str) -> Option<f32> {
+        let device = self.find_device(device_id)?;
+        unsafe { device.meter.GetPeakValue().ok() }
+    }
+
+    fn set_default_device(&mut self, device_id: &str) -> bool {
+        if self.find_device(device_id).is_none() {
+            return false;
+        }
+
+        if let Err(e) = policy_config::set_default_endpoint(device_id) {
+            log::error!("Failed to set default device {}: {}", device_id, e);
+            return false;
+        }
+
+        // Master, the session list and the default flags belong to the old device until reloaded
+        self.reload();
+        true
+    }
+
+    fn set_session_device(&mut self, session_id: &str, device_id: Option<&str>) -> bool {
+        let Some(pid) = self.find(session_id).map(|session| session.pid).filter(|pid| *pid != 0) else {
+            return false;
+        };
+        if let Some(device_id) = device_id {
+            if !self.find_device(device_id).is_some_and(|device| device.kind == DeviceKind::Output) {
+                return false;
+            }
+        }
+
+        if let Err(e) = policy_config::set_app_endpoint(pid, device_id) {
+            log::error!("Failed to route session {} to {:?}: {}", session_id, device_id, e);
+            return false;
+        }
+
+        // The app's session moves over to the new device
+        self.reload();
+        true
+    }
+
+    fn set_mute(&mut self, session_id: &str, mute: bool) -> bool {
+        let result = unsafe {
+            if session_id == MASTER_SESSION {
+                self.master
+                    .as_ref()
+                    .map(|master| master.SetMute(mute, std::ptr::null()))
+            } else {
+                self.find(session_id)
+                    .map(|session| session.volume.SetMute(mute, std::ptr::null()))
+            }
+        };
+
+        log_result(result, "mute", session_id)
+    }
+
+    fn peak(&self, session_id: &str) -> Option<f32> {
+        let meter = if session_id == MASTER_SESSION {
+            self.master_meter.as_ref()?
+        } else {
+            &self.find(session_id)?.meter
+        };
+        unsafe { meter.GetPeakValue().ok() }
+    }
+}
+
+// None means nothing matched the id
+fn log_result(result: Option<windows::core::Result<()>>, setting: &str, id: &str) -> bool {
+    match result {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            log::error!("Failed to set {} of {}: {}", setting, id, e);
+            false
+        }
+        None => false,
+    }
+}
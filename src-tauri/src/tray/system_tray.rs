@@ -3,30 +3,35 @@ use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use tauri::image::Image;
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Listener, Manager, Wry,
 };
 
-use crate::api::events::AppEvent;
+use crate::api::events::{self, AppEvent};
+use crate::models::audio_device::DeviceKind;
+use crate::services::{device_service, profile_service, state_service};
 use crate::{services::window_service, utils};
 
 static WINDOW_LAST_HIDDEN: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
 
-pub fn initialize_tray(app_handle: AppHandle<Wry>) {
-    let version = app_handle.package_info().version.to_string();
+// Menu ids of output devices are this prefix followed by the endpoint id
+const DEVICE_ITEM_PREFIX: &str = "device:";
+// Likewise for profiles and the profile name
+const PROFILE_ITEM_PREFIX: &str = "profile:";
 
-    let version_item = MenuItem::with_id(&app_handle, "version", format!("Version: {}", version), false, None::<&str>).unwrap();
-    let open_logs = MenuItem::with_id(&app_handle, "show_logs", "Logs", true, None::<&str>).unwrap();
-    let quit = MenuItem::with_id(&app_handle, "quit", "Quit", true, None::<&str>).unwrap();
-    let settings = MenuItem::with_id(&app_handle, "settings", "Settings", true, None::<&str>).unwrap();
-
-    let menu = Menu::with_items(&app_handle, &[&version_item, &settings, &open_logs, &quit]).unwrap();
+pub fn initialize_tray(app_handle: AppHandle<Wry>) {
+    let menu = build_menu(&app_handle);
 
     app_handle.listen(AppEvent::WindowHidden.as_str(), |_| {
         *WINDOW_LAST_HIDDEN.lock().unwrap() = Some(Instant::now());
     });
 
+    app_handle.listen(AppEvent::DeviceChange.as_str(), {
+        let app_handle = app_handle.clone();
+        move |_| refresh_menu(&app_handle)
+    });
+
     let image = Image::from_path("icons/speaker-32.png").unwrap();
 
     let _ = TrayIconBuilder::with_id("tray")
@@ -52,11 +57,35 @@ pub fn initialize_tray(app_handle: AppHandle<Wry>) {
             }
             "quit" => {
                 log::info!("Quitting Maestro");
+                state_service::flush();
                 app.exit(0);
             }
-            _ => {}
+            id => {
+                if let Some(device_id) = id.strip_prefix(DEVICE_ITEM_PREFIX) {
+                    log::info!("Switching default device from tray");
+                    match device_service::set_default_device(device_id) {
+                        Some(device) => events::emit_device_change_event(&device, app.clone()),
+                        // Keep the check mark on the device that is still the default
+                        None => refresh_menu(app),
+                    }
+                } else if let Some(name) = id.strip_prefix(PROFILE_ITEM_PREFIX) {
+                    log::info!("Applying profile from tray");
+                    profile_service::apply_profile(name, app.clone());
+                }
+            }
         })
         .on_tray_icon_event(|tray, event| {
+            // Devices can be plugged in or switched elsewhere, so list them fresh before the menu opens
+            if let TrayIconEvent::Click {
+                button: MouseButton::Right,
+                button_state: MouseButtonState::Down,
+                ..
+            } = event
+            {
+                refresh_menu(tray.app_handle());
+                return;
+            }
+
             if let TrayIconEvent::Click {
                 button: MouseButton::Left,
                 button_state: MouseButtonState::Down,
@@ -70,7 +99,7 @@ pub fn initialize_tray(app_handle: AppHandle<Wry>) {
                 let should_ignore = WINDOW_LAST_HIDDEN
                     .lock()
                     .unwrap()
-                    .map_or(false, |last_hidden| last_hidden.elapsed() < grace_period);
+                    .is_some_and(|last_hidden| last_hidden.elapsed() < grace_period);
 
                 if should_ignore {
                     log::debug!("Ignoring tray click due to recent window hide");
@@ -88,3 +117,65 @@ pub fn initialize_tray(app_handle: AppHandle<Wry>) {
         })
         .build(&app_handle);
 }
+
+fn build_menu(app_handle: &AppHandle<Wry>) -> Menu<Wry> {
+    let version = app_handle.package_info().version.to_string();
+
+    let version_item = MenuItem::with_id(app_handle, "version", format!("Version: {}", version), false, None::<&str>).unwrap();
+    let devices = build_devices_menu(app_handle);
+    let profiles = build_profiles_menu(app_handle);
+    let open_logs = MenuItem::with_id(app_handle, "show_logs", "Logs", true, None::<&str>).unwrap();
+    let quit = MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>).unwrap();
+    let settings = MenuItem::with_id(app_handle, "settings", "Settings", true, None::<&str>).unwrap();
+
+    Menu::with_items(app_handle, &[&version_item, &devices, &profiles, &settings, &open_logs, &quit]).unwrap()
+}
+
+fn build_devices_menu(app_handle: &AppHandle<Wry>) -> Submenu<Wry> {
+    let submenu = Submenu::with_id(app_handle, "devices", "Output Device", true).unwrap();
+
+    let outputs = device_service::get_devices()
+        .into_iter()
+        .filter(|device| device.kind == DeviceKind::Output);
+    for device in outputs {
+        let item = CheckMenuItem::with_id(
+            app_handle,
+            format!("{}{}", DEVICE_ITEM_PREFIX, device.id),
+            &device.name,
+            true,
+            device.is_default,
+            None::<&str>,
+        )
+        .unwrap();
+        let _ = submenu.append(&item);
+    }
+
+    submenu
+}
+
+fn build_profiles_menu(app_handle: &AppHandle<Wry>) -> Submenu<Wry> {
+    let profiles = profile_service::get_profiles();
+    let submenu = Submenu::with_id(app_handle, "profiles", "Profiles", !profiles.is_empty()).unwrap();
+
+    for profile in profiles {
+        let item = MenuItem::with_id(
+            app_handle,
+            format!("{}{}", PROFILE_ITEM_PREFIX, profile.name),
+            &profile.name,
+            true,
+            None::<&str>,
+        )
+        .unwrap();
+        let _ = submenu.append(&item);
+    }
+
+    submenu
+}
+
+fn refresh_menu(app_handle: &AppHandle<Wry>) {
+    if let Some(tray) = app_handle.tray_by_id("tray") {
+        if let Err(e) = tray.set_menu(Some(build_menu(app_handle))) {
+            log::error!("Failed to refresh tray menu: {}", e);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::thread;
+use std::time::Duration;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetAsyncKeyState, VIRTUAL_KEY, VK_BACK, VK_CAPITAL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_HOME, VK_INSERT, VK_LEFT,
+    VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_MEDIA_STOP, VK_NEXT, VK_NUMLOCK, VK_NUMPAD0, VK_OEM_1, VK_OEM_2,
+    VK_OEM_3, VK_OEM_4, VK_OEM_5, VK_OEM_6, VK_OEM_7, VK_OEM_COMMA, VK_OEM_MINUS, VK_OEM_PERIOD, VK_OEM_PLUS, VK_PAUSE, VK_PRIOR,
+    VK_RETURN, VK_RIGHT, VK_SCROLL, VK_SNAPSHOT, VK_SPACE, VK_TAB, VK_UP, VK_VOLUME_DOWN, VK_VOLUME_MUTE, VK_VOLUME_UP,
+};
+
+use crate::models::hotkey::Hotkey;
+use crate::utils::keybind_registry::{KeyCallback, ReleaseWatcher};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Finds out when a key is let go by polling its state, since the key listener only reports presses.
+pub struct KeyStatePoller;
+
+impl ReleaseWatcher for KeyStatePoller {
+    fn watch_release(&self, hotkey: &str, callback: KeyCallback) {
+        // Modifiers are often let go first, so only the main key decides when the hold ends
+        let key = hotkey.parse::<Hotkey>().ok().and_then(|hotkey| virtual_key(&hotkey.key));
+        if key.is_none() {
+            log::warn!("Can't watch {} for release, treating it as released", hotkey);
+        }
+
+        // Never call back on the hook thread, release handlers may wait out a delay
+        thread::spawn(move || {
+            while key.is_some_and(is_down) {
+                thread::sleep(POLL_INTERVAL);
+            }
+            callback();
+        });
+    }
+}
+
+fn is_down(key: VIRTUAL_KEY) -> bool {
+    // The high bit is set while the key is down
+    unsafe { GetAsyncKeyState(key.0 as i32) < 0 }
+}
+
+// Takes the canonical key names Hotkey parsing produces
+fn virtual_key(key: &str) -> Option<VIRTUAL_KEY> {
+    if key.len() == 1 {
+        let c = key.bytes().next()?;
+        // Letters and digits share their virtual key code with ASCII
+        if c.is_ascii_alphanumeric() {
+            return Some(VIRTUAL_KEY(c as u16));
+        }
+        let key = match c {
+            b';' => VK_OEM_1,
+            b'/' => VK_OEM_2,
+            b'`' => VK_OEM_3,
+            b'[' => VK_OEM_4,
+            b'\\' => VK_OEM_5,
+            b']' => VK_OEM_6,
+            b'\'' => VK_OEM_7,
+            b',' => VK_OEM_COMMA,
+            b'-' => VK_OEM_MINUS,
+            b'.' => VK_OEM_PERIOD,
+            b'=' => VK_OEM_PLUS,
+            _ => return None,
+        };
+        return Some(key);
+    }
+
+    if let Some(number) = key.strip_prefix('F').and_then(|n| n.parse::<u16>().ok()) {
+        return Some(VIRTUAL_KEY(VK_F1.0 + number - 1));
+    }
+
+    if let Some(digit) = key.strip_prefix("Numpad").and_then(|n| n.parse::<u16>().ok()) {
+        return Some(VIRTUAL_KEY(VK_NUMPAD0.0 + digit));
+    }
+
+    let key = match key {
+        "Space" => VK_SPACE,
+        "Enter" => VK_RETURN,
+        "Tab" => VK_TAB,
+        "Escape" => VK_ESCAPE,
+        "Backspace" => VK_BACK,
+        "Delete" => VK_DELETE,
+        "Insert" => VK_INSERT,
+        "Home" => VK_HOME,
+        "End" => VK_END,
+        "PageUp" => VK_PRIOR,
+        "PageDown" => VK_NEXT,
+        "Up" => VK_UP,
+        "Down" => VK_DOWN,
+        "Left" => VK_LEFT,
+        "Right" => VK_RIGHT,
+        "PrintScreen" => VK_SNAPSHOT,
+        "Pause" => VK_PAUSE,
+        "CapsLock" => VK_CAPITAL,
+        "NumLock" => VK_NUMLOCK,
+        "ScrollLock" => VK_SCROLL,
+        "VolumeUp" => VK_VOLUME_UP,
+        "VolumeDown" => VK_VOLUME_DOWN,
+        "VolumeMute" => VK_VOLUME_MUTE,
+        "MediaPlayPause" => VK_MEDIA_PLAY_PAUSE,
+        "MediaNextTrack" => VK_MEDIA_NEXT_TRACK,
+        "MediaPrevTrack" => VK_MEDIA_PREV_TRACK,
+        "MediaStop" => VK_MEDIA_STOP,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,400 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+#[cfg(windows)]
+use std::time::Duration;
+
+#[cfg(windows)]
+use windows_key_listener::KeyListener;
+
+#[cfg(windows)]
+use super::key_state::KeyStatePoller;
+
+#[cfg(windows)]
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(25);
+
+pub type KeyCallback = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// Anything that can deliver global hotkey presses.
+///
+/// Hooks can't be removed once installed, so the registry installs at most one per
+/// hotkey and decides on each press whether a binding is still active.
+pub trait KeySource {
+    fn listen(&self, hotkey: &str, callback: KeyCallback) -> Result<(), String>;
+    /// Used for bindings that also act when their key is let go.
+    fn release_watcher(&self) -> Arc<dyn ReleaseWatcher>;
+}
+
+/// Calls back once a hotkey that was just pressed is let go.
+pub trait ReleaseWatcher: Send + Sync {
+    fn watch_release(&self, hotkey: &str, callback: KeyCallback);
+}
+
+#[cfg(windows)]
+impl KeySource for KeyListener {
+    fn listen(&self, hotkey: &str, callback: KeyCallback) -> Result<(), String> {
+        KeyListener::listen(self, hotkey, DEBOUNCE_INTERVAL, callback).map_err(|e| e.to_string())
+    }
+
+    fn release_watcher(&self) -> Arc<dyn ReleaseWatcher> {
+        Arc::new(KeyStatePoller)
+    }
+}
+
+/// Stands in for the key listener on platforms without global hotkeys, where every binding fails to register.
+#[cfg(not(windows))]
+#[derive(Default)]
+pub struct NoKeys;
+
+#[cfg(not(windows))]
+impl NoKeys {
+    pub fn new() -> Self {
+        NoKeys
+    }
+}
+
+#[cfg(not(windows))]
+impl KeySource for NoKeys {
+    fn listen(&self, _hotkey: &str, _callback: KeyCallback) -> Result<(), String> {
+        Err("global hotkeys are only supported on Windows".to_string())
+    }
+
+    fn release_watcher(&self) -> Arc<dyn ReleaseWatcher> {
+        Arc::new(NoKeys)
+    }
+}
+
+#[cfg(not(windows))]
+impl ReleaseWatcher for NoKeys {
+    fn watch_release(&self, _hotkey: &str, _callback: KeyCallback) {}
+}
+
+#[derive(Clone)]
+pub struct Binding {
+    pub id: String,
+    pub hotkey: String,
+    pub handler: KeyCallback,
+    pub release: Option<KeyCallback>,
+}
+
+impl Binding {
+    pub fn new(id: impl Into<String>, hotkey: impl Into<String>, handler: KeyCallback) -> Self {
+        Binding {
+            id: id.into(),
+            hotkey: hotkey.into(),
+            handler,
+            release: None,
+        }
+    }
+
+    /// Also run `release` when the key is let go. Key repeat no longer re-runs the handler while it's held.
+    pub fn with_release(mut self, release: KeyCallback) -> Self {
+        self.release = Some(release);
+        self
+    }
+
+    fn label(&self) -> String {
+        format!("{} [{}]", self.id, self.hotkey)
+    }
+}
+
+/// Outcome of applying a new set of bindings.
+#[derive(Debug, Default, PartialEq)]
+pub struct RegistryReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Owns every hotkey binding and replaces the whole set on reload.
+pub struct KeybindRegistry<S: KeySource> {
+    source: S,
+    watcher: Arc<dyn ReleaseWatcher>,
+    hooked: HashSet<String>,
+    active: Arc<Mutex<HashMap<String, Binding>>>,
+}
+
+impl<S: KeySource> KeybindRegistry<S> {
+    pub fn new(source: S) -> Self {
+        KeybindRegistry {
+            watcher: source.release_watcher(),
+            source,
+            hooked: HashSet::new(),
+            active: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn apply(&mut self, bindings: Vec<Binding>) -> RegistryReport {
+        let mut report = RegistryReport::default();
+        let mut next: HashMap<String, Binding> = HashMap::new();
+
+        for binding in bindings {
+            let hotkey = normalize_hotkey(&binding.hotkey);
+
+            if let Some(existing) = next.get(&hotkey) {
+                let reason = format!("{} is already bound to {}", binding.hotkey, existing.id);
+                report.failed.push((binding.label(), reason));
+                continue;
+            }
+
+            if !self.hooked.contains(&hotkey) {
+                if let Err(e) = self.source.listen(&binding.hotkey, self.dispatcher(&hotkey)) {
+                    report.failed.push((binding.label(), e));
+                    continue;
+                }
+                self.hooked.insert(hotkey.clone());
+            }
+
+            next.insert(hotkey, binding);
+        }
+
+        let mut active = self.active.lock().unwrap();
+
+        let previous: HashSet<String> = active.values().map(Binding::label).collect();
+        let current: HashSet<String> = next.values().map(Binding::label).collect();
+        report.added = current.difference(&previous).cloned().collect();
+        report.removed = previous.difference(&current).cloned().collect();
+        report.added.sort();
+        report.removed.sort();
+
+        *active = next;
+
+        report
+    }
+
+    fn dispatcher(&self, hotkey: &str) -> KeyCallback {
+        let active = Arc::clone(&self.active);
+        let watcher = Arc::clone(&self.watcher);
+        let held = Arc::new(AtomicBool::new(false));
+        let hotkey = hotkey.to_string();
+        Arc::new(move || {
+            // Release the lock before running the handler so it can't deadlock a reload
+            let binding = active.lock().unwrap().get(&hotkey).cloned();
+            let Some(binding) = binding else {
+                return false;
+            };
+            let Some(release) = binding.release else {
+                return (binding.handler)();
+            };
+
+            // Key repeat keeps reporting presses for as long as the key is down
+            if held.swap(true, Ordering::SeqCst) {
+                return true;
+            }
+
+            let consumed = (binding.handler)();
+            let held = Arc::clone(&held);
+            watcher.watch_release(
+                &binding.hotkey,
+                Arc::new(move || {
+                    held.store(false, Ordering::SeqCst);
+                    release()
+                }),
+            );
+            consumed
+        })
+    }
+}
+
+fn normalize_hotkey(hotkey: &str) -> String {
+    hotkey
+        .split('+')
+        .map(|part| part.trim().to_lowercase())
+        .collect::<Vec<String>>()
+        .join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct FakeKeySource {
+        hooks: Arc<Mutex<HashMap<String, KeyCallback>>>,
+        listen_calls: Arc<AtomicUsize>,
+        rejected: Vec<String>,
+        watcher: Arc<FakeReleaseWatcher>,
+    }
+
+    // Holds on to release callbacks until the test lets go of the key
+    #[derive(Default)]
+    struct FakeReleaseWatcher {
+        pending: Mutex<Vec<KeyCallback>>,
+    }
+
+    impl FakeReleaseWatcher {
+        fn release_all(&self) {
+            let pending: Vec<KeyCallback> = self.pending.lock().unwrap().drain(..).collect();
+            for callback in pending {
+                callback();
+            }
+        }
+    }
+
+    impl ReleaseWatcher for FakeReleaseWatcher {
+        fn watch_release(&self, _hotkey: &str, callback: KeyCallback) {
+            self.pending.lock().unwrap().push(callback);
+        }
+    }
+
+    impl FakeKeySource {
+        fn press(hooks: &Mutex<HashMap<String, KeyCallback>>, hotkey: &str) -> bool {
+            let callback = hooks.lock().unwrap().get(&normalize_hotkey(hotkey)).cloned();
+            callback.is_some_and(|callback| callback())
+        }
+    }
+
+    impl KeySource for FakeKeySource {
+        fn listen(&self, hotkey: &str, callback: KeyCallback) -> Result<(), String> {
+            self.listen_calls.fetch_add(1, Ordering::SeqCst);
+            if self.rejected.iter().any(|rejected| rejected == hotkey) {
+                return Err(format!("unknown key {}", hotkey));
+            }
+            self.hooks.lock().unwrap().insert(normalize_hotkey(hotkey), callback);
+            Ok(())
+        }
+
+        fn release_watcher(&self) -> Arc<dyn ReleaseWatcher> {
+            self.watcher.clone()
+        }
+    }
+
+    fn counting_binding(id: &str, hotkey: &str, counter: &Arc<AtomicUsize>) -> Binding {
+        let counter = Arc::clone(counter);
+        Binding::new(
+            id,
+            hotkey,
+            Arc::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                true
+            }),
+        )
+    }
+
+    #[test]
+    fn reload_replaces_instead_of_stacking_handlers() {
+        let source = FakeKeySource::default();
+        let hooks = Arc::clone(&source.hooks);
+        let listen_calls = Arc::clone(&source.listen_calls);
+        let mut registry = KeybindRegistry::new(source);
+
+        let first = Arc::new(AtomicUsize::new(0));
+        let second = Arc::new(AtomicUsize::new(0));
+        registry.apply(vec![counting_binding("master:volume_up", "VolumeUp", &first)]);
+        registry.apply(vec![counting_binding("master:volume_up", "VolumeUp", &second)]);
+
+        assert!(FakeKeySource::press(&hooks, "VolumeUp"));
+        assert_eq!(first.load(Ordering::SeqCst), 0);
+        assert_eq!(second.load(Ordering::SeqCst), 1);
+        assert_eq!(listen_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn removed_bindings_stop_firing() {
+        let source = FakeKeySource::default();
+        let hooks = Arc::clone(&source.hooks);
+        let mut registry = KeybindRegistry::new(source);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        registry.apply(vec![counting_binding("mixer", "Ctrl + Shift + M", &counter)]);
+        let report = registry.apply(vec![]);
+
+        assert_eq!(report.removed, vec!["mixer [Ctrl + Shift + M]".to_string()]);
+        assert!(!FakeKeySource::press(&hooks, "ctrl+shift+m"));
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn report_lists_added_removed_and_failed() {
+        let source = FakeKeySource {
+            rejected: vec!["Hyper + Q".to_string()],
+            ..Default::default()
+        };
+        let mut registry = KeybindRegistry::new(source);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        registry.apply(vec![
+            counting_binding("mixer", "Ctrl + Shift + M", &counter),
+            counting_binding("master:volume_up", "VolumeUp", &counter),
+        ]);
+        let report = registry.apply(vec![
+            counting_binding("master:volume_up", "VolumeUp", &counter),
+            counting_binding("chrome:toggle_mute", "Ctrl + F9", &counter),
+            counting_binding("discord:toggle_mute", "ctrl+f9", &counter),
+            counting_binding("spotify:volume_up", "Hyper + Q", &counter),
+        ]);
+
+        assert_eq!(report.added, vec!["chrome:toggle_mute [Ctrl + F9]".to_string()]);
+        assert_eq!(report.removed, vec!["mixer [Ctrl + Shift + M]".to_string()]);
+        assert_eq!(report.failed.len(), 2);
+        assert_eq!(report.failed[0].0, "discord:toggle_mute [ctrl+f9]");
+        assert_eq!(
+            report.failed[1],
+            ("spotify:volume_up [Hyper + Q]".to_string(), "unknown key Hyper + Q".to_string())
+        );
+    }
+
+    #[test]
+    fn failed_hook_is_retried_on_next_apply() {
+        let source = FakeKeySource {
+            rejected: vec!["F13".to_string()],
+            ..Default::default()
+        };
+        let listen_calls = Arc::clone(&source.listen_calls);
+        let mut registry = KeybindRegistry::new(source);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        registry.apply(vec![counting_binding("master:toggle_mute", "F13", &counter)]);
+        registry.apply(vec![counting_binding("master:toggle_mute", "F13", &counter)]);
+
+        assert_eq!(listen_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn held_binding_presses_once_and_releases_once() {
+        let source = FakeKeySource::default();
+        let hooks = Arc::clone(&source.hooks);
+        let watcher = Arc::clone(&source.watcher);
+        let mut registry = KeybindRegistry::new(source);
+
+        let presses = Arc::new(AtomicUsize::new(0));
+        let releases = Arc::new(AtomicUsize::new(0));
+        let binding = counting_binding("master:push_to_talk", "F13", &presses).with_release({
+            let releases = Arc::clone(&releases);
+            Arc::new(move || {
+                releases.fetch_add(1, Ordering::SeqCst);
+                true
+            })
+        });
+        registry.apply(vec![binding]);
+
+        // Key repeat while held
+        assert!(FakeKeySource::press(&hooks, "F13"));
+        assert!(FakeKeySource::press(&hooks, "F13"));
+        assert_eq!(presses.load(Ordering::SeqCst), 1);
+        assert_eq!(releases.load(Ordering::SeqCst), 0);
+
+        watcher.release_all();
+        assert_eq!(releases.load(Ordering::SeqCst), 1);
+
+        FakeKeySource::press(&hooks, "F13");
+        assert_eq!(presses.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn bindings_without_release_fire_on_every_press() {
+        let source = FakeKeySource::default();
+        let hooks = Arc::clone(&source.hooks);
+        let watcher = Arc::clone(&source.watcher);
+        let mut registry = KeybindRegistry::new(source);
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        registry.apply(vec![counting_binding("master:volume_up", "VolumeUp", &counter)]);
+        FakeKeySource::press(&hooks, "VolumeUp");
+        FakeKeySource::press(&hooks, "VolumeUp");
+
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        assert!(watcher.pending.lock().unwrap().is_empty());
+    }
+}
@@ -13,9 +13,9 @@ pub fn init() {
             log::Level::Trace => "\x1b[35m", // Magenta
         };
 
-        write!(
+        writeln!(
             write,
-            "{}{} [{}] - {} - {}\x1b[0m\n",
+            "{}{} [{}] - {} - {}\x1b[0m",
             color_code,
             now.now().format("%Y-%m-%d %H:%M:%S"),
             level,
@@ -61,7 +61,7 @@ fn delete_old_log_files(keep: usize) {
         return;
     }
 
-    log_files.sort_by(|a, b| b.0.cmp(&a.0));
+    log_files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
 
     for (_, path) in log_files.into_iter().skip(keep) {
         log::debug!("Deleting old log file: {}", path.display());
@@ -70,7 +70,8 @@ fn delete_old_log_files(keep: usize) {
 }
 
 pub fn open_log_file() {
-    Command::new("explorer").arg("logs").spawn().unwrap();
+    // Explorer hands the folder to its running instance and exits right away
+    Command::new("explorer").arg("logs").status().unwrap();
 }
 
 pub fn log(message: String, level: &str) {
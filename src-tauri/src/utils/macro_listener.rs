@@ -1,43 +1,93 @@
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use tauri::AppHandle;
 use tauri::Event;
 use tauri::Listener;
+#[cfg(windows)]
 use windows_key_listener::KeyListener;
 
 use crate::api::events;
 use crate::api::events::AppEvent;
 use crate::config;
 use crate::config::Config;
-use crate::services::volume_service;
+use crate::models::keybind_action::KeybindAction;
+use crate::services::volume_step::{Repeats, Step};
 use crate::services::window_service;
+use crate::services::{device_service, profile_service, volume_service};
+#[cfg(not(windows))]
+use crate::utils::keybind_registry::NoKeys as KeyListener;
+use crate::utils::keybind_registry::{Binding, KeyCallback, KeybindRegistry};
 
-const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(25);
+// Bumped on every push-to-talk/mute press, so a release only lands if the key wasn't pressed again during its delay
+static HOLD_GENERATION: AtomicU64 = AtomicU64::new(0);
 
 pub fn initialize_key_listeners(app_handle: AppHandle) {
-    let key_listener = KeyListener::new();
+    let registry = Arc::new(Mutex::new(KeybindRegistry::new(KeyListener::new())));
 
-    register_key_listeners(app_handle.clone(), &key_listener, config::get_config());
+    apply_key_bindings(&registry, app_handle.clone(), &config::get_config());
 
     app_handle.listen(AppEvent::ConfigChange.as_str(), {
         let app_handle = app_handle.clone();
         move |event: Event| {
             if let Ok(config) = serde_json::from_str::<Config>(event.payload()) {
-                log::info!("Config changed, resetting key listeners");
-                let new_listener = KeyListener::new();
-                register_key_listeners(app_handle.clone(), &new_listener, config);
+                log::info!("Config changed, reloading key bindings");
+                apply_key_bindings(&registry, app_handle.clone(), &config);
             }
         }
     });
 }
 
-fn register_key_listeners(app_handle: AppHandle, key_listener: &KeyListener, config: Config) {
-    if let Some(hotkey) = config.mixer.hotkey {
-        log::info!("Registering mixer hotkey: {}", hotkey);
-        if let Err(e) = key_listener.listen(
-            &hotkey,
-            DEBOUNCE_INTERVAL,
+fn apply_key_bindings(registry: &Mutex<KeybindRegistry<KeyListener>>, app_handle: AppHandle, config: &Config) {
+    let report = registry.lock().unwrap().apply(build_key_bindings(app_handle.clone(), config));
+
+    for binding in &report.added {
+        log::info!("Keybind added: {}", binding);
+    }
+    for binding in &report.removed {
+        log::info!("Keybind removed: {}", binding);
+    }
+    for (binding, reason) in &report.failed {
+        log::error!("Failed to register keybind {}: {}", binding, reason);
+    }
+
+    // The mic waits in the state its hold key moves it out of, or push to talk would be live until first used
+    if let Some(mute) = idle_mic_mute(config) {
+        log::info!("Mic idles {}", if mute { "muted" } else { "live" });
+        if let Some(device) = device_service::set_mic_mute(mute) {
+            events::emit_mic_mute_change_event(&device, app_handle);
+        }
+    }
+}
+
+// Muted with a push to talk key bound, live with push to mute. Push to talk wins if both are, erring on the quiet side.
+fn idle_mic_mute(config: &Config) -> Option<bool> {
+    let actions: Vec<KeybindAction> = config
+        .sessions
+        .iter()
+        .flat_map(|session| session.keybinds.iter().flatten())
+        .chain(config.groups.iter().flat_map(|group| group.keybinds.iter().flatten()))
+        .filter_map(|keybind| keybind.action.parse().ok())
+        .collect();
+
+    if actions.iter().any(|action| matches!(action, KeybindAction::PushToTalk(_))) {
+        return Some(true);
+    }
+    if actions.iter().any(|action| matches!(action, KeybindAction::PushToMute(_))) {
+        return Some(false);
+    }
+    None
+}
+
+fn build_key_bindings(app_handle: AppHandle, config: &Config) -> Vec<Binding> {
+    let mut bindings = Vec::new();
+
+    if let Some(hotkey) = &config.mixer.hotkey {
+        bindings.push(Binding::new(
+            "mixer",
+            hotkey,
             Arc::new({
                 let app_handle = app_handle.clone();
                 move || {
@@ -45,73 +95,192 @@ fn register_key_listeners(app_handle: AppHandle, key_listener: &KeyListener, con
                     true
                 }
             }),
-        ) {
-            log::error!("Failed to register mixer hotkey: {}", e);
-        }
+        ));
     }
 
-    if let Err(e) = key_listener.listen(
+    // Media keys follow the master entry's step when it has one
+    let master_step = config
+        .sessions
+        .iter()
+        .find(|session| session.name.eq_ignore_ascii_case("master"))
+        .and_then(|session| session.step);
+    let step = Step::resolve(&config.step, master_step, None);
+
+    bindings.push(action_binding(
+        "master",
         "VolumeUp",
-        DEBOUNCE_INTERVAL,
-        Arc::new({
-            let app_handle = app_handle.clone();
-            move || {
-                handle_session_up("master", app_handle.clone());
-                true
+        KeybindAction::VolumeUp,
+        step,
+        app_handle.clone(),
+    ));
+    bindings.push(action_binding(
+        "master",
+        "VolumeDown",
+        KeybindAction::VolumeDown,
+        step,
+        app_handle.clone(),
+    ));
+    bindings.push(action_binding(
+        "master",
+        "VolumeMute",
+        KeybindAction::ToggleMute,
+        step,
+        app_handle.clone(),
+    ));
+
+    for session in &config.sessions {
+        for keybind in session.keybinds.iter().flatten() {
+            let step = Step::resolve(&config.step, session.step, keybind.step);
+            match keybind.action.parse::<KeybindAction>() {
+                Ok(action) => bindings.push(action_binding(&session.name, &keybind.key, action, step, app_handle.clone())),
+                Err(e) => log::error!("Rejected keybind {} for session {}: {}", keybind.key, session.name, e),
             }
-        }),
-    ) {
-        log::error!("Failed to register VolumeUp hotkey: {}", e);
+        }
     }
 
-    if let Err(e) = key_listener.listen(
-        "VolumeDown",
-        DEBOUNCE_INTERVAL,
-        Arc::new({
-            let app_handle = app_handle.clone();
-            move || {
-                handle_session_down("master", app_handle.clone());
-                true
+    for group in &config.groups {
+        for keybind in group.keybinds.iter().flatten() {
+            let step = Step::resolve(&config.step, group.step, keybind.step);
+            match keybind.action.parse::<KeybindAction>() {
+                Ok(action) => bindings.push(action_binding(&group.name, &keybind.key, action, step, app_handle.clone())),
+                Err(e) => log::error!("Rejected keybind {} for group {}: {}", keybind.key, group.name, e),
             }
-        }),
-    ) {
-        log::error!("Failed to register VolumeDown hotkey: {}", e);
+        }
     }
 
-    if let Err(e) = key_listener.listen(
-        "VolumeMute",
-        DEBOUNCE_INTERVAL,
+    bindings
+}
+
+fn action_binding(session_name: &str, hotkey: &str, action: KeybindAction, step: Step, app_handle: AppHandle) -> Binding {
+    let id = format!("{}:{}", session_name, action);
+    let binding = Binding::new(
+        id,
+        hotkey,
         Arc::new({
+            let session_name = session_name.to_string();
+            let action = action.clone();
             let app_handle = app_handle.clone();
+            let repeats = Mutex::new(Repeats::default());
             move || {
-                handle_session_toggle_mute("master", app_handle.clone());
+                let step = step.at(repeats.lock().unwrap().press(Instant::now()));
+                handle_keybind_action(&session_name, action.clone(), step, app_handle.clone());
                 true
             }
         }),
-    ) {
-        log::error!("Failed to register VolumeMute hotkey: {}", e);
+    );
+
+    match action {
+        KeybindAction::PushToTalk(delay) => binding.with_release(mic_hold_release(true, delay, app_handle)),
+        KeybindAction::PushToMute(delay) => binding.with_release(mic_hold_release(false, delay, app_handle)),
+        _ => binding,
+    }
+}
+
+fn handle_keybind_action(session_name: &str, action: KeybindAction, step: i32, app_handle: AppHandle) {
+    match action {
+        KeybindAction::VolumeUp => handle_session_up(session_name, step, app_handle),
+        KeybindAction::VolumeDown => handle_session_down(session_name, step, app_handle),
+        KeybindAction::ToggleMute => handle_session_toggle_mute(session_name, app_handle),
+        KeybindAction::SetVolume(volume) => handle_session_set(session_name, volume, app_handle),
+        KeybindAction::CycleDevice => handle_cycle_device(app_handle),
+        KeybindAction::ToggleMicMute => handle_mic_toggle_mute(app_handle),
+        KeybindAction::PushToTalk(_) => handle_mic_hold(false, app_handle),
+        KeybindAction::PushToMute(_) => handle_mic_hold(true, app_handle),
+        KeybindAction::Profile(name) => {
+            profile_service::apply_profile(&name, app_handle);
+        }
+    }
+}
+
+fn handle_mic_hold(mute: bool, app_handle: AppHandle) {
+    HOLD_GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Some(device) = device_service::set_mic_mute(mute) {
+        events::emit_mic_mute_change_event(&device, app_handle);
+    }
+}
+
+// Runs on the release watcher's thread, so waiting out the delay here doesn't hold up other keys
+fn mic_hold_release(mute: bool, delay: u64, app_handle: AppHandle) -> KeyCallback {
+    Arc::new(move || {
+        let generation = HOLD_GENERATION.load(Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(delay));
+
+        if HOLD_GENERATION.load(Ordering::SeqCst) != generation {
+            return true;
+        }
+        if let Some(device) = device_service::set_mic_mute(mute) {
+            events::emit_mic_mute_change_event(&device, app_handle.clone());
+        }
+        true
+    })
+}
+
+fn handle_mic_toggle_mute(app_handle: AppHandle) {
+    if let Some(device) = device_service::toggle_mic_mute() {
+        events::emit_mic_mute_change_event(&device, app_handle);
+    }
+}
+
+fn handle_cycle_device(app_handle: AppHandle) {
+    if let Some(device) = device_service::cycle_default_device() {
+        events::emit_device_change_event(&device, app_handle);
     }
 }
 
 fn handle_session_toggle_mute(session_name: &str, app_handle: AppHandle) {
-    let session = volume_service::toggle_session_mute(session_name);
+    if let Some(session) = volume_service::toggle_session_mute(session_name) {
+        events::emit_volume_change_event(&session, app_handle);
+    }
+}
 
-    events::emit_volume_change_event(&session, app_handle);
+fn handle_session_set(session_name: &str, volume: i32, app_handle: AppHandle) {
+    if let Some(session) = volume_service::set_session_volume(session_name, volume) {
+        events::emit_volume_change_event(&session, app_handle);
+    }
+}
+
+fn handle_session_up(session_name: &str, step: i32, app_handle: AppHandle) {
+    if let Some(session) = volume_service::step_session_volume(session_name, step) {
+        events::emit_volume_change_event(&session, app_handle);
+    }
 }
 
-fn handle_session_up(session_name: &str, app_handle: AppHandle) {
-    let current_vol = volume_service::get_session_volume(session_name);
-    
-    volume_service::set_session_volume(session_name, current_vol + 2).unwrap();
-    volume_service::set_session_mute(session_name, false);
-    
-    let updated_session = volume_service::get_sessions(session_name).into_iter().next().unwrap();
-    events::emit_volume_change_event(&updated_session, app_handle);
+fn handle_session_down(session_name: &str, step: i32, app_handle: AppHandle) {
+    if let Some(session) = volume_service::step_session_volume(session_name, -step) {
+        events::emit_volume_change_event(&session, app_handle);
+    }
 }
 
-fn handle_session_down(session_name: &str, app_handle: AppHandle) {
-    let current_vol = volume_service::get_session_volume(session_name);
-    let session = volume_service::set_session_volume(session_name, current_vol - 2).unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{KeybindConfig, SessionConfig};
+
+    fn config(actions: &[&str]) -> Config {
+        let mut config = Config::default();
+        let mut mic = SessionConfig::new("mic");
+        mic.keybinds = Some(
+            actions
+                .iter()
+                .enumerate()
+                .map(|(index, action)| KeybindConfig {
+                    key: format!("F{}", index + 13),
+                    action: action.to_string(),
+                    step: None,
+                })
+                .collect(),
+        );
+        config.sessions.push(mic);
+        config
+    }
 
-    events::emit_volume_change_event(&session, app_handle);
+    #[test]
+    fn mic_idles_opposite_to_its_hold_key() {
+        assert_eq!(idle_mic_mute(&config(&["push_to_talk"])), Some(true));
+        assert_eq!(idle_mic_mute(&config(&["push_to_mute:0"])), Some(false));
+        assert_eq!(idle_mic_mute(&config(&["push_to_mute", "push_to_talk:500"])), Some(true));
+        // Without a hold key the mic is left as it is
+        assert_eq!(idle_mic_mute(&config(&["toggle_mic_mute"])), None);
+        assert_eq!(idle_mic_mute(&Config::default()), None);
+    }
 }
@@ -3,7 +3,9 @@
 use crate::api::events::AppEvent;
 use crate::config::{get_config, Config};
 use tauri::{AppHandle, Listener};
+#[cfg(windows)]
 use windows::Win32::Foundation::HWND;
+#[cfg(windows)]
 use windows::Win32::System::Console::{AllocConsole, AttachConsole, FreeConsole, GetConsoleWindow, ATTACH_PARENT_PROCESS};
 
 
@@ -37,6 +39,7 @@ pub fn handle_enable_autostart(app_handle: AppHandle) {
     });
 }
 
+#[cfg(windows)]
 pub fn handle_debug_console(app_handle: AppHandle) {
     unsafe fn set_debug_console(config: Config) {
         if config.system.show_console {
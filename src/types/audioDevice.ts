@@ -0,0 +1,8 @@
+export interface AudioDevice {
+  id: string;
+  name: string;
+  kind: "output" | "input";
+  volume: number;
+  mute: boolean;
+  is_default: boolean;
+}
@@ -1,6 +1,12 @@
 export interface AudioSession {
+  id: string;
   name: string;
+  pid: number;
+  exe_path: string | null;
+  device_id: string | null;
   volume: number;
+  // null for master, whose level Windows scales itself
+  decibels: number | null;
   mute: boolean;
   icon: string | undefined;
 }
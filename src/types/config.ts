@@ -1,17 +1,82 @@
 export interface Config {
+  version: number;
   sessions: SessionConfig[];
+  groups?: GroupConfig[];
+  profiles?: ProfileConfig[];
+  ducking?: DuckingConfig[];
+  step?: StepConfig;
+  volume?: VolumeConfig;
   mixer: MixerConfig;
   system: SystemConfig;
 }
 
 export interface SessionConfig {
   name: string;
-  encoder: number;
+  keybinds: KeybindConfig[] | null;
+  aliases?: string[];
+  exe?: string;
+  title?: string;
+  pid?: number;
+  instance_id?: string;
+  output_device?: string;
+  remember?: boolean;
+  step?: number;
+}
+
+export interface GroupConfig {
+  name: string;
+  members: string[];
+  mode?: "relative" | "absolute";
+  keybinds: KeybindConfig[] | null;
+  step?: number;
+}
+
+export interface ProfileLevel {
+  name: string;
+  volume: number;
+  mute: boolean;
+}
+
+export interface ProfileConfig {
+  name: string;
+  sessions: ProfileLevel[];
+  fade?: number;
+}
+
+export interface DuckingConfig {
+  trigger: string;
+  targets: string[];
+  amount?: number;
+  hold?: number;
+  threshold?: number;
+}
+
+export interface KeybindConfig {
+  key: string;
+  action: string;
+  step?: number;
+}
+
+export interface StepConfig {
+  size: number;
+  // Largest step while a key is held
+  max?: number;
+}
+
+export type VolumeDisplay = "percent" | "db";
+
+export interface VolumeConfig {
+  curve?: "linear" | "logarithmic" | "custom";
+  // [slider, volume] pairs for the custom curve
+  points?: [number, number][];
+  display?: VolumeDisplay;
 }
 
 export interface MixerConfig {
   enabled: boolean;
   hotkey: string;
+  // Peak meter updates per second, 0 turns the meters off
+  meter_rate?: number;
 }
 
 
@@ -20,3 +85,18 @@ export interface SystemConfig {
   show_console: boolean;
   theme: string;
 }
+
+export interface ConfigError {
+  kind: "io" | "parse" | "migration" | "validation";
+  path: string;
+  line: number | null;
+  column: number | null;
+  message: string;
+  backup_path: string | null;
+}
+
+export interface Diagnostic {
+  path: string;
+  severity: "error" | "warning";
+  message: string;
+}
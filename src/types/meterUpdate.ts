@@ -0,0 +1,9 @@
+export interface MeterLevel {
+  id: string;
+  peak: number;
+}
+
+export interface MeterUpdate {
+  sessions: MeterLevel[];
+  devices: MeterLevel[];
+}
@@ -1,5 +1,6 @@
-import { Config } from "@/types/config";
+import { Config, ConfigError, Diagnostic, ProfileConfig } from "@/types/config";
 import { invoke } from "@tauri-apps/api/core";
+import { AudioDevice } from "../types/audioDevice";
 import { AudioSession } from "../types/audioSession";
 
 export enum Command {
@@ -7,8 +8,20 @@ export enum Command {
   GetSession = "get_session",
   SetSessionVolume = "set_session_volume",
   ToggleSessionMute = "toggle_session_mute",
+  GetDevices = "get_devices",
+  GetDevice = "get_device",
+  SetDeviceVolume = "set_device_volume",
+  SetDeviceMute = "set_device_mute",
+  ToggleDeviceMute = "toggle_device_mute",
+  SetDefaultDevice = "set_default_device",
+  SetSessionDevice = "set_session_device",
+  GetProfiles = "get_profiles",
+  SaveProfile = "save_profile",
+  ApplyProfile = "apply_profile",
   GetConfig = "get_config",
   SetConfig = "set_config",
+  GetConfigError = "get_config_error",
+  ValidateConfig = "validate_config",
   GetTaskbarHeight = "get_taskbar_height",
 }
 
@@ -17,18 +30,42 @@ export interface CommandArgs {
   [Command.GetSession]: { sessionName: string };
   [Command.SetSessionVolume]: { sessionName: string; volume: number };
   [Command.ToggleSessionMute]: { sessionName: string };
+  [Command.GetDevices]: undefined;
+  [Command.GetDevice]: { deviceId: string };
+  [Command.SetDeviceVolume]: { deviceId: string; volume: number };
+  [Command.SetDeviceMute]: { deviceId: string; mute: boolean };
+  [Command.ToggleDeviceMute]: { deviceId: string };
+  [Command.SetDefaultDevice]: { deviceId: string };
+  [Command.SetSessionDevice]: { sessionId: string; deviceId: string | null };
+  [Command.GetProfiles]: undefined;
+  [Command.SaveProfile]: { name: string };
+  [Command.ApplyProfile]: { name: string };
   [Command.GetConfig]: undefined;
   [Command.SetConfig]: { config: Config };
+  [Command.GetConfigError]: undefined;
+  [Command.ValidateConfig]: { config: Config };
   [Command.GetTaskbarHeight]: undefined;
 }
 
 export interface CommandReturns {
   [Command.GetAllSessions]: AudioSession[];
   [Command.GetSession]: AudioSession;
-  [Command.SetSessionVolume]: void;
-  [Command.ToggleSessionMute]: void;
+  [Command.SetSessionVolume]: AudioSession | null;
+  [Command.ToggleSessionMute]: boolean;
+  [Command.GetDevices]: AudioDevice[];
+  [Command.GetDevice]: AudioDevice | null;
+  [Command.SetDeviceVolume]: AudioDevice | null;
+  [Command.SetDeviceMute]: boolean;
+  [Command.ToggleDeviceMute]: boolean;
+  [Command.SetDefaultDevice]: AudioDevice | null;
+  [Command.SetSessionDevice]: boolean;
+  [Command.GetProfiles]: ProfileConfig[];
+  [Command.SaveProfile]: Diagnostic[];
+  [Command.ApplyProfile]: boolean;
   [Command.GetConfig]: Config;
-  [Command.SetConfig]: void;
+  [Command.SetConfig]: Diagnostic[];
+  [Command.GetConfigError]: ConfigError | null;
+  [Command.ValidateConfig]: Diagnostic[];
   [Command.GetTaskbarHeight]: number;
 }
 
@@ -1,16 +1,33 @@
 import { listen } from "@tauri-apps/api/event";
+import { AudioDevice } from "../types/audioDevice";
 import { AudioSession } from "../types/audioSession";
+import { ConfigError } from "../types/config";
+import { MeterUpdate } from "../types/meterUpdate";
 
 export enum AppEvent {
   VolumeChange = "volume-change-event",
+  SessionAdded = "session-added-event",
+  SessionRemoved = "session-removed-event",
+  DeviceVolumeChange = "device-volume-change-event",
+  DeviceChange = "device-change-event",
+  MicMuteChange = "mic-mute-change-event",
   MixerVisibilityChange = "mixer-visibility-change-event",
   ThemeChange = "theme-change-event",
+  ConfigError = "config-error-event",
+  MeterUpdate = "meter-update-event",
 }
 
 export interface EventPayloads {
   [AppEvent.VolumeChange]: AudioSession;
+  [AppEvent.SessionAdded]: AudioSession;
+  [AppEvent.SessionRemoved]: AudioSession;
+  [AppEvent.DeviceVolumeChange]: AudioDevice;
+  [AppEvent.DeviceChange]: AudioDevice;
+  [AppEvent.MicMuteChange]: AudioDevice;
   [AppEvent.MixerVisibilityChange]: boolean;
   [AppEvent.ThemeChange]: string;
+  [AppEvent.ConfigError]: ConfigError;
+  [AppEvent.MeterUpdate]: MeterUpdate;
 }
 
 export function listenToEvent<T extends AppEvent>(event: T, callback: (payload: EventPayloads[T]) => void): Promise<() => void> {
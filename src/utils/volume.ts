@@ -0,0 +1,15 @@
+import type { VolumeDisplay } from "../types/config";
+
+// Matches MIN_DECIBELS on the Rust side, which is what silence reports
+const MIN_DECIBELS = -96;
+
+// Channels without a dB level, like master and devices, always show percent
+export function formatVolume(volume: number, decibels: number | null | undefined, display: VolumeDisplay): string {
+  if (display !== "db" || decibels === null || decibels === undefined) {
+    return `${volume}`;
+  }
+  if (decibels <= MIN_DECIBELS) {
+    return "-∞";
+  }
+  return `${Math.round(decibels)}`;
+}